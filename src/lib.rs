@@ -1,942 +1,4191 @@
-use std::collections::HashMap;
-use std::{env, i32};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
 use std::fs::{self, File};
 use std::io::{self, BufReader, Read};
-use std::fmt;
+use std::fmt::{self, Write};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::mem;
+use std::ops::{Index, IndexMut};
+use std::num::IntErrorKind;
 
 use anyhow::{anyhow, Result};
 
-#[derive(Default, Copy,Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Num {
-    
-    Uint(u32),
-    Int(i32),
-    Flt(f32),
-    #[default]
-    Empty,
+
+    Uint(u64),
+    Int(i64),
+    Flt(f64),
+    // Only produced in arbitrary precision mode, for numbers too large for any
+    // of the above. The digits are still available from the Number.
+    Big,
 }
 
 
-#[derive(Default, Copy, Clone)]
+// In arbitrary precision mode the text is kept as the exact value, and is
+// empty otherwise.
+#[derive(Clone, Debug)]
 pub struct Number {
     value : Num,
-    digit : u32,
-    is_negative : bool
+    text : String,
 }
 
-#[derive(Default, Clone)]
-pub struct Booler {
-    value : bool,
-    current: String,
-    correct: String,
-    index : usize,
+#[derive(Copy, Clone)]
+pub struct ParserOptions {
+    // Keep every number's literal digits so it can be read back or written
+    // out exactly, and accept numbers that don't fit in 64 bits.
+    pub arbitrary_precision : bool,
+    // Reject everything RFC 8259 rejects. Without it trailing commas, leading
+    // zeros, raw control characters in strings and form feeds as whitespace
+    // are let through.
+    pub strict : bool,
+    // What to do when an object repeats a key.
+    pub duplicate_keys : DuplicateKeys,
+    // How many objects and arrays may be open at once. Anything deeper is
+    // rejected, since dropping or printing a value recurses once per level
+    // and would run out of stack.
+    pub max_depth : usize,
 }
 
-#[derive(Default)]
-pub enum Member {
-    Str(String),
-    Num(Number),  
-    Bool(Booler),
-    Arr(Vec<Member>),
-    JSON(JSON),
-    #[default]
-    Empty
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            arbitrary_precision : false,
+            strict : false,
+            duplicate_keys : DuplicateKeys::default(),
+            max_depth : 128,
+        }
+    }
 }
 
-pub struct JSON {
-    pub map :  HashMap<String, Member>,
-    current_object : bool,
-    is_json : bool,
-}
-pub struct Array {
-    value : Vec<Member>,
-    data : Member,
-    is_array : bool,
-    is_escaped : bool
+#[derive(Default, Copy, Clone, PartialEq)]
+pub enum DuplicateKeys {
+    // Fail with ParseError::DuplicateKey pointing at the repeated key.
+    Error,
+    KeepFirst,
+    // The later value replaces the earlier one, which keeps its place.
+    #[default]
+    KeepLast,
+    // Gather every value for the key into an array, in the order they came.
+    // Keys that appear once get an array too, so a repeated key can't be
+    // mistaken for one that held an array.
+    Collect,
 }
 
-pub struct JSONPair {
-    key: String,
-    value : Member,
-    current_key : bool, 
-    current_value : bool,
-    is_escaped: bool
+// How to_string_pretty_with lays out its output.
+#[derive(Copy, Clone)]
+pub struct PrettyOptions {
+    pub indent : Indent,
+    // Write "key": value rather than "key":value.
+    pub space_after_colon : bool,
+    // End the output with a newline, as most editors save files.
+    pub trailing_newline : bool,
 }
 
-impl std::fmt::Display for Member {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Member::Bool(booly) => {
-                write!(f,"{}",booly.value)
-            }
-            Member::Str(string) => {
-                write!(f,"{}",string)
-            }
-            Member::Num(nummer) =>{
-                match nummer.value {
-                    Num::Uint(uint) => {
-                        write!(f, "{}", uint)
-                    }
-                    Num::Int(int) => {
-                        write!(f, "{}", int)
-                    }
-                    Num::Flt(flt) => {
-                        write!(f,"{}", flt)
-                    }
-                    _ =>{
-                        write!(f,"Empty number")
-                    }
-                }
-            }
-            Member::Arr(_) => {
+// What one level of nesting is indented with.
+#[derive(Copy, Clone)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs(usize),
+}
 
-                write!(f,"[ Might be something in here ]")
-            }
-            _ => {
-                write!(f,"Not quite done yet")
-            }
+impl Default for PrettyOptions {
+    fn default() -> PrettyOptions {
+        PrettyOptions {
+            indent : Indent::Spaces(4),
+            space_after_colon : true,
+            trailing_newline : false,
         }
     }
 }
 
+// Where in the input something went wrong. Lines and columns count from 1 and
+// columns count characters, while the offset counts bytes from the start.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Position {
+    pub line : usize,
+    pub column : usize,
+    pub offset : usize,
+}
 
-pub fn get_reader() -> Result<BufReader<File>> {
-    
-    let args : Vec<String> = env::args().collect();
+// Everything the parser can fail with. The functions that spot a problem only
+// see a single character, so they leave the position empty and the read loop
+// fills it in on the way out.
+#[derive(Debug)]
+pub enum ParseError {
+    UnexpectedCharacter { found : char, expected : String, position : Position },
+    UnexpectedEof { expected : String, position : Position },
+    InvalidEscape { sequence : String, position : Position },
+    InvalidUtf8 { byte : u8, position : Position },
+    InvalidNumber { text : String, position : Position },
+    NumberOverflow { text : String, position : Position },
+    InvalidLiteral { found : String, expected : String, position : Position },
+    ControlCharacter { found : char, position : Position },
+    NotAnObject { position : Position },
+    DuplicateKey { key : String, position : Position },
+    TooDeep { max_depth : usize, position : Position },
+    Io { error : io::Error, position : Position },
+}
 
-    let file_path = parse_args(&args);
+impl Number {
+    // Whether the number was an integer or a float, and its value.
+    pub fn value(&self) -> Num {
+        self.value
+    }
 
-    let reader = read_file(&file_path);
+    // The number exactly as it was written. Only kept in arbitrary precision
+    // mode.
+    pub fn as_str(&self) -> Option<&str> {
+        if self.text.is_empty() {
+            return None;
+        }
 
-    reader
-}
+        Some(&self.text)
+    }
 
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.as_str() {
+            Some(text) => text.parse().ok(),
+            None => match self.value {
+                Num::Uint(uint) => Some(uint as u128),
+                Num::Int(int) => u128::try_from(int).ok(),
+                _ => None,
+            },
+        }
+    }
 
-fn parse_args(input_args : &[String]) -> &str {
+    pub fn to_i128(&self) -> Option<i128> {
+        match self.as_str() {
+            Some(text) => text.parse().ok(),
+            None => match self.value {
+                Num::Uint(uint) => Some(uint as i128),
+                Num::Int(int) => Some(int as i128),
+                _ => None,
+            },
+        }
+    }
 
-    let file_path = match input_args.get(1) {
-        Some(file_path) => {
-            file_path
-        },
-        None => {
-            panic!("No JSON file path provided");
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.value {
+            Num::Uint(uint) => i64::try_from(uint).ok(),
+            Num::Int(int) => Some(int),
+            _ => None,
         }
-    };
+    }
 
-    match file_path.split('.').last() {
-        Some(file_type) => {
-            if file_type != "json" {
-                panic!("This is not a valid JSON file");
-            }
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.value {
+            Num::Uint(uint) => Some(uint),
+            Num::Int(int) => u64::try_from(int).ok(),
+            _ => None,
         }
-        None => {
-            panic!("There's an error in the file path provided");
+    }
+
+    pub fn to_f64(&self) -> Option<f64> {
+        match self.value {
+            Num::Uint(uint) => Some(uint as f64),
+            Num::Int(int) => Some(int as f64),
+            Num::Flt(flt) => Some(flt),
+            // Past the largest double the nearest one is infinity, which is
+            // no use as an approximation.
+            Num::Big => self.as_str().and_then(|text| text.parse().ok()).filter(|flt : &f64| flt.is_finite()),
         }
-    };
+    }
+}
 
-    
+// Members compare structurally, with numbers compared by value (see Number)
+// and objects regardless of key order. Hashing follows the same rules, so any
+// Member can be used as a map key.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Member {
+    Str(String),
+    Num(Number),
+    Bool(bool),
+    Arr(Vec<Member>),
+    JSON(JSON),
+    #[default]
+    Null,
+}
 
-    file_path
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct JSON {
+    pub map :  Map,
 }
 
-fn read_file(file_path : &str) -> Result<BufReader<File>>  {
-    
-    let file = fs::File::open(file_path)?;
+// An object's members in the order they were written, so printing a parsed
+// document gives them back the way they came in. Lookups by key go through a
+// hash index kept alongside.
+#[derive(Default, Clone)]
+pub struct Map {
+    entries : Vec<(String, Member)>,
+    index : HashMap<String, usize>,
+}
 
-    let reader = io::BufReader::new(file);
+pub struct Iter<'a> {
+    entries : std::slice::Iter<'a, (String, Member)>,
+}
 
-    Ok(reader)
+// A key looked up for editing, which may or may not be in the map yet.
+pub enum Entry<'a> {
+    Occupied(&'a mut Member),
+    Vacant(&'a mut Map, String),
 }
 
-pub fn parse_json<'a>(reader : &'a mut BufReader<File>) -> Result<JSON> {
-   
+// Everything a parse keeps track of from one character to the next. Values
+// are only built once their last character has arrived, so none of this ends
+// up in the Members handed back.
+struct Parser {
+    options : ParserOptions,
+    stack : Vec<Frame>,
+    decoder : Utf8Decoder,
+    // The position of the next character, which is where any error is reported.
+    position : Position,
+    // Bytes of a multi-byte character already pushed into the decoder.
+    pending : usize,
+}
 
-    let mut json = JSON {
-        map : HashMap::new(),
-        current_object : false,
-        is_json : false,
-    };
+// A value whose characters are still arriving.
+enum Partial {
+    Str(String, Escape),
+    Num(String),
+    // A literal being spelled out, and how many of its letters have arrived.
+    Literal(&'static str, usize),
+    // Complete, so only whitespace or a delimiter may follow.
+    Done(Member),
+}
 
-    let mut json_pair = JSONPair {
-            key : String::new(),
-            value : Member::Empty,
-            current_key : false,
-            current_value : false,
-            is_escaped : false
-    };
+struct ObjectState {
+    json : JSON,
+    key : String,
+    in_key : bool,
+    key_escape : Escape,
+    // Where the current key's opening quote was, for DuplicateKey.
+    key_start : Position,
+    value : Option<Partial>,
+    expect : Expect,
+}
 
-    let mut arr  = Array {
-        value : Vec::new(),
-        data : Member::Empty,
-        is_array : false,
-        is_escaped : false
-    };
+struct ArrayState {
+    values : Vec<Member>,
+    value : Option<Partial>,
+    expect : Expect,
+}
 
-    let mut curr_u8 : [u8;1] = [65u8];
+// Which token a frame is waiting for next, outside of any key or value.
+#[derive(Default, Copy, Clone, PartialEq)]
+enum Expect {
+    // Just opened, so the closing bracket may come straight away.
+    #[default]
+    Start,
+    // After a ',' in an object.
+    Key,
+    // After a key.
+    Colon,
+    // After a ':' or a ',' in an array, and for as long as the value lasts.
+    Value,
+}
 
-    loop {
-        
-        let _len = reader.read(&mut curr_u8)?;
+// Where we are inside an escape sequence of a string. Surrogate pairs span two
+// \uXXXX escapes, so the high half is carried along until the low half shows up.
+#[derive(Default, Copy, Clone)]
+enum Escape {
+    #[default]
+    None,
+    Backslash { high : Option<u32> },
+    Unicode { code : u32, digits : u32, high : Option<u32> },
+    LowSurrogate(u32),
+}
 
-        if _len == 0 {
-            break
+impl Escape {
+    fn in_progress(&self) -> bool {
+        !matches!(self, Escape::None)
+    }
+}
+
+// Everything needed to keep parsing one open object or array. Nested values
+// push a new frame and hand their finished Member back to the parent when they
+// close. The bottom of the stack is always a Root frame holding the top-level
+// value itself.
+enum Frame {
+    Root(Option<Partial>),
+    Object(ObjectState),
+    Array(ArrayState),
+}
+
+// Collects the bytes of one UTF-8 encoded character at a time, since the
+// reader hands us raw bytes that may split a character across two reads.
+struct Utf8Decoder {
+    bytes : [u8;4],
+    len : usize,
+    expected : usize,
+}
+
+impl Utf8Decoder {
+    fn new() -> Utf8Decoder {
+        Utf8Decoder {
+            bytes : [0u8;4],
+            len : 0,
+            expected : 0,
+        }
+    }
+
+    fn push(&mut self, byte : u8) -> Result<Option<char>, ParseError> {
+
+        if self.len == 0 {
+            self.expected = match byte {
+                0x00..=0x7F => return Ok(Some(byte as char)),
+                0xC2..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF4 => 4,
+                _ => return Err(ParseError::InvalidUtf8 { byte, position : Position::default() }),
+            };
         }
+        else if byte & 0xC0 != 0x80 {
+            return Err(ParseError::InvalidUtf8 { byte, position : Position::default() });
+        }
+
+        self.bytes[self.len] = byte;
+        self.len += 1;
+
+        if self.len < self.expected {
+            return Ok(None);
+        }
+
+        let len = mem::take(&mut self.len);
+
+        let decoded = std::str::from_utf8(&self.bytes[..len])
+            .map_err(|_| ParseError::InvalidUtf8 { byte, position : Position::default() })?
+            .chars()
+            .next();
+
+        Ok(decoded)
+    }
 
-        let char = curr_u8[0] as char;
+    fn finish(&self) -> Result<(), ParseError> {
+        if self.len != 0 {
+            return Err(ParseError::eof("the rest of a UTF-8 character"));
+        }
 
-        parse_character(char, &mut json_pair, &mut json, &mut arr)?;
+        Ok(())
     }
+}
 
+impl JSON {
+    pub fn new() -> JSON {
+        JSON {
+            map : Map::new(),
+        }
+    }
 
-    json.map.insert(json_pair.key, json_pair.value);
+    pub fn get(&self, key : &str) -> Option<&Member> {
+        self.map.get(key)
+    }
 
-    
-    if !json.is_json && json.map.is_empty() {
-        return Err(anyhow!("No JSON found"));
+    pub fn get_mut(&mut self, key : &str) -> Option<&mut Member> {
+        self.map.get_mut(key)
+    }
+
+    pub fn insert(&mut self, key : impl Into<String>, member : Member) -> Option<Member> {
+        self.map.insert(key.into(), member)
+    }
+
+    pub fn remove(&mut self, key : &str) -> Option<Member> {
+        self.map.remove(key)
     }
 
-    Ok(json)
+    pub fn entry(&mut self, key : impl Into<String>) -> Entry<'_> {
+        self.map.entry(key)
+    }
 }
 
-fn parse_character(current_char : char, json_pair : &mut JSONPair, json : &mut JSON, array : &mut Array) -> Result<()> {
+// What indexing hands back for a key or element that isn't there, so lookups
+// can be chained without checking every step.
+static NULL : Member = Member::Null;
 
-    if !current_char.is_ascii_whitespace() && !json.current_object && current_char != '{' {
-        return Err(anyhow!("No JSON object has been started"))
+impl Member {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Member::Str(string) => Some(string),
+            _ => None,
+        }
     }
 
-    match current_char {
-        '{' => {
-            json.current_object = true;
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Member::Bool(booly) => Some(*booly),
+            _ => None,
         }
-        '}' => {
-            if json.current_object {
-                json.current_object = false;
-                json.is_json = true;
+    }
 
-                
-            }
-            else {
-                return Err(anyhow!("No JSON object to close")) 
-            }
+    // Only whole numbers that fit, never a float cut down to one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Member::Num(number) => number.as_i64(),
+            _ => None,
         }
-        '[' => {
-            if json_pair.current_value {
-                array.is_array = true;
-            }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Member::Num(number) => number.as_u64(),
+            _ => None,
         }
-        ']' => {
-            if json_pair.current_value {
-                array.is_array = false;
-                json_pair.current_value = false;
-
-                match array.data {
-                    Member::Empty => (),
-                    _ => {
-                        array.value.push(mem::take(&mut array.data));
-                    }
-                }
+    }
 
-                json_pair.value = Member::Arr(mem::take(&mut array.value));
-            }
+    // Any number, as near as a double gets to it.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Member::Num(number) => number.to_f64(),
+            _ => None,
         }
-        '"' => {
-            if array.is_array {
-                match array.data {
-                    Member::Empty => {
-                        (array.data, array.is_escaped) = checkset_string(&mut array.data, array.is_escaped, current_char)?;
-                    }
-                    Member::Str(_)=>{
-                        if array.is_escaped {
-                            (array.data, array.is_escaped) = checkset_string(&mut array.data, array.is_escaped, current_char)?;
-                        }
-                    }
-                    _ =>()
-                }
-            }
-            else if json_pair.current_value {
-                match json_pair.value {
-                    Member::Empty => {
-                        (json_pair.value, json_pair.is_escaped) = checkset_string(&mut json_pair.value, json_pair.is_escaped, current_char)?;
-                    }
-                    Member::Str(_)=>{
-                        if json_pair.is_escaped {
-                            (json_pair.value, json_pair.is_escaped) = checkset_string(&mut json_pair.value, json_pair.is_escaped, current_char)?;
-                        }
-                        else {
-                            json_pair.current_value = false;
-                        }
-                    }
-                    _ =>()
-                }
-            }
-            else {
-                check_key(json_pair)?;
-            }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Member>> {
+        match self {
+            Member::Arr(array) => Some(array),
+            _ => None,
         }
-        ':' => {
-            if json_pair.key.len() > 0 {
-                json_pair.current_value = true;
-            }    
+    }
+
+    pub fn as_object(&self) -> Option<&JSON> {
+        match self {
+            Member::JSON(json) => Some(json),
+            _ => None,
         }
-        ',' => {
-            if array.is_array {
-                array.value.push(mem::replace(&mut array.data,Member::Empty));
-            }
-            else {
-                json.map.insert(mem::replace(&mut json_pair.key, String::new()), mem::replace(&mut json_pair.value, Member::Empty));
-            
-                json_pair.current_value = false;
-            };
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Member::Null)
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Member>> {
+        match self {
+            Member::Arr(array) => Some(array),
+            _ => None,
         }
-        '.' => {
-            if array.is_array {
-               array.data = make_flt(&mut array.data)?;
-            }
-            else if json_pair.current_value {
-                json_pair.value =  make_flt(&mut json_pair.value)?;
-            }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut JSON> {
+        match self {
+            Member::JSON(json) => Some(json),
+            _ => None,
         }
-        _ => {
-            if current_char.is_ascii_whitespace() && ((!json_pair.current_value && !json_pair.current_key)) {
-                return Ok(());
-            }
+    }
+
+    pub fn get_mut(&mut self, key : &str) -> Option<&mut Member> {
+        self.as_object_mut()?.get_mut(key)
+    }
 
-            if json.current_object {
+    // Editing null as an object or array makes it one first, the way
+    // assigning to doc["a"]["b"] creates the objects along the way. Any other
+    // value is left alone rather than thrown away.
+    fn object_mut(&mut self) -> Option<&mut JSON> {
+        if self.is_null() {
+            *self = Member::JSON(JSON::new());
+        }
 
-                if json_pair.current_value  || array.is_array {
+        self.as_object_mut()
+    }
 
-                    let mut current_mem : Member;
+    fn array_mut(&mut self) -> Option<&mut Vec<Member>> {
+        if self.is_null() {
+            *self = Member::Arr(Vec::new());
+        }
 
-                    if array.is_array {
-                        current_mem = mem::take(&mut array.data);
-                    }
-                    else {
-                        current_mem = mem::take(&mut json_pair.value);
-                    }
+        self.as_array_mut()
+    }
 
-                    match current_mem {
-                        Member::Empty => {
-                            if array.is_array {
-                               array.data = create_value(current_char)?;
-                            }
-                            else {
-                                json_pair.value = create_value(current_char)?;
-                            }
-                        }
-                        _ => {
-                            if array.is_array {
-                                (array.data, array.is_escaped) = fill_value(current_char, &mut current_mem, array.is_escaped)?;
-                            }
-                            else {
-                                (json_pair.value, json_pair.is_escaped) = fill_value(current_char, &mut current_mem, json_pair.is_escaped)?;
-                            }
-                        }
-                    }
-                }
-                else if json_pair.current_key {
-                    fill_key(current_char, json_pair)?;
-                }
-            }
+    // What kind of value this is, for messages about using it as another.
+    fn kind(&self) -> &'static str {
+        match self {
+            Member::Str(_) => "a string",
+            Member::Num(_) => "a number",
+            Member::Bool(_) => "a boolean",
+            Member::Arr(_) => "an array",
+            Member::JSON(_) => "an object",
+            Member::Null => "null",
         }
     }
 
-    Ok(())
-}
+    pub fn insert(&mut self, key : impl Into<String>, member : Member) -> Result<Option<Member>> {
+        let kind = self.kind();
+
+        match self.object_mut() {
+            Some(json) => Ok(json.insert(key, member)),
+            None => Err(anyhow!("Can't insert '{}' into {}", key.into(), kind)),
+        }
+    }
 
-fn create_value(current_char : char) -> Result<Member> {
+    pub fn remove(&mut self, key : &str) -> Option<Member> {
+        self.as_object_mut()?.remove(key)
+    }
 
-    let mut new_data : Member = Member::Empty;
-    
-    if current_char == 't' || current_char == 'f' {
-        new_data = match current_char {
-            't' => {
-                let truer = Booler {
-                    value: true,
-                    current : String::from("t"),
-                    correct : String::from("true"),
-                    index : 1
-                };
-                Member::Bool(truer)
-            }
-            _ => {
-                let falser = Booler {
-                    value : false,
-                    current : String::from("f"),
-                    correct : String::from("false"),
-                    index : 1
-                };
+    pub fn push(&mut self, member : Member) -> Result<()> {
+        let kind = self.kind();
 
-                Member::Bool(falser)
+        match self.array_mut() {
+            Some(array) => {
+                array.push(member);
+                Ok(())
             }
-        };
+            None => Err(anyhow!("Can't push onto {}", kind)),
+        }
     }
-    else if current_char.is_digit(10) || current_char == '-' {
 
-        let mut num = Number {
-            value : Num::Uint(0),
-            is_negative : false,
-            digit: 0,
-        };
+    pub fn entry(&mut self, key : impl Into<String>) -> Result<Entry<'_>> {
+        let kind = self.kind();
 
-        new_data = match current_char {
-            '-' => {
+        match self.object_mut() {
+            Some(json) => Ok(json.entry(key)),
+            None => Err(anyhow!("Can't look up '{}' in {}", key.into(), kind)),
+        }
+    }
+}
 
-                let int = Number {
-                    value : Num::Int(0),
-                    is_negative : true,
-                    digit: 0,
-                };
+impl Index<&str> for Member {
+    type Output = Member;
 
-                Member::Num(int)
-            }
-            _ => {
-                num.value = Num::Uint(current_char.to_digit(10).expect("This needs to be a number"));
-                num.digit += 1;
-                Member::Num(num)
-            }
-        };
-    } 
+    fn index(&self, key : &str) -> &Member {
+        match self {
+            Member::JSON(json) => &json[key],
+            _ => &NULL,
+        }
+    }
+}
 
-    Ok(new_data)
+impl Index<usize> for Member {
+    type Output = Member;
+
+    fn index(&self, index : usize) -> &Member {
+        match self {
+            Member::Arr(array) => array.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
 }
 
-fn fill_key(current_char : char, json_pair : &mut JSONPair) -> Result<()> {
-    
-    if json_pair.current_key {
-        json_pair.key.push(current_char);
-   }
-    else {
-        return Err(anyhow!("Problem with key"));
+impl Index<&str> for JSON {
+    type Output = Member;
+
+    fn index(&self, key : &str) -> &Member {
+        self.map.get(key).unwrap_or(&NULL)
     }
+}
 
-    Ok(())
+// A missing key is added as null, ready to be assigned to.
+impl IndexMut<&str> for JSON {
+    fn index_mut(&mut self, key : &str) -> &mut Member {
+        self.map.entry(key).or_insert(Member::Null)
+    }
 }
 
-fn fill_value(current_char : char, data : &mut Member, escaped : bool) -> Result<(Member,bool)> {
-    let is_escaped = escaped;
+// Like assignment, indexing can't report an error, so using a value that is
+// neither null nor an object this way panics instead of replacing it.
+impl IndexMut<&str> for Member {
+    fn index_mut(&mut self, key : &str) -> &mut Member {
+        let kind = self.kind();
 
-    match data {
-               Member::Str(str) => {
+        match self.object_mut() {
+            Some(json) => &mut json[key],
+            None => panic!("Can't index into {} with '{}'", kind, key),
+        }
+    }
+}
 
-                    let (new_string,is_escaped ) = append_character(current_char, str, escaped)?;
-                    return Ok((Member::Str(new_string),is_escaped));
-                }
-               Member::Num(num) => {
-                    if current_char.is_ascii_whitespace() {
-                        return Ok((Member::Num(mem::take(num)),is_escaped));
-                    }
-                    
-                   let base : i32 = 10;
+// Indexing one past the end appends a null, ready to be assigned to. Anything
+// further out panics, as does a value that is neither null nor an array.
+impl IndexMut<usize> for Member {
+    fn index_mut(&mut self, index : usize) -> &mut Member {
+        let kind = self.kind();
 
-                   let sign : i32 = if num.is_negative {-1} else {1};
+        let array = match self.array_mut() {
+            Some(array) => array,
+            None => panic!("Can't index into {} with {}", kind, index),
+        };
 
-                   match num.value {
-                        Num::Uint(uint) => {
-                            num.value = Num::Uint(uint*(base.pow(num.digit)as u32)  + current_char.to_digit(10).expect("This should be a number"));
-                            num.digit += 1;
-                        }
-                        Num::Int(int) => {
-                            if num.digit == 0 {
-                                num.value = Num::Int(int+(current_char.to_digit(10).expect("This  should be a number") as i32)*sign);
-                                num.digit += 1;
-                            }
-                            else {
-                                num.value = Num::Int(int*base.pow(num.digit) + (current_char.to_digit(10).expect("This should be a number") as i32)*sign);
-                                num.digit += 1;
-                            }
+        if index == array.len() {
+            array.push(Member::Null);
+        }
+
+        let len = array.len();
+
+        match array.get_mut(index) {
+            Some(member) => member,
+            None => panic!("Index {} is past the end of an array of length {}", index, len),
+        }
+    }
+}
+
+impl Number {
+    // A number without any digits kept. Num::Big has nothing to go on without
+    // them, so that gives None and from_digits builds those instead.
+    pub fn new(value : Num) -> Option<Number> {
+        match value {
+            Num::Big => None,
+            _ => Some(Number::of(value)),
+        }
+    }
+
+    // A number written out as JSON, with its digits kept exactly as they are
+    // in arbitrary precision mode.
+    pub fn from_digits(text : &str) -> Result<Number, ParseError> {
+        let options = ParserOptions { arbitrary_precision : true, strict : true, ..ParserOptions::default() };
+
+        let value = parse_number(text, &options).map_err(|err| err.at(Position::start()))?;
+
+        Ok(Number { value, text : text.to_string() })
+    }
+
+    fn of(value : Num) -> Number {
+        Number {
+            value,
+            text : String::new(),
+        }
+    }
+
+    // The value as an integer, for floats only when they hold a whole number.
+    fn integer(&self) -> Option<i128> {
+        match self.value {
+            Num::Uint(uint) => Some(uint as i128),
+            Num::Int(int) => Some(int as i128),
+            // Every integral float below 2^127 converts to i128 exactly, -0.0
+            // included.
+            Num::Flt(flt) if flt.fract() == 0.0 && flt.abs() < 2f64.powi(127) => Some(flt as i128),
+            _ => None,
+        }
+    }
+
+    // Holds a number without kept digits against digits that were kept. A
+    // float matches both the shortest decimal it prints as and the exact
+    // binary value it holds.
+    fn matches_digits(&self, text : &str) -> bool {
+        let key = Some(digits_key(text));
+
+        match self.value {
+            Num::Uint(uint) => exact(&uint.to_string()) == key,
+            Num::Int(int) => exact(&int.to_string()) == key,
+            // 767 places are enough to write out any f64 exactly.
+            Num::Flt(flt) if flt.is_finite() => {
+                exact(&format!("{:e}", flt)) == key || exact(&format!("{:.767e}", flt)) == key
+            }
+            _ => false,
+        }
+    }
+
+    // The double nearest the number. Numbers that are equal always round to
+    // the same one, which makes it cheap to hash by.
+    fn rounded(&self) -> f64 {
+        let rounded = match (self.as_str(), self.value) {
+            (Some(text), _) => text.parse().unwrap_or(f64::NAN),
+            (None, Num::Uint(uint)) => uint as f64,
+            (None, Num::Int(int)) => int as f64,
+            (None, Num::Flt(flt)) => flt,
+            (None, Num::Big) => f64::NAN,
+        };
+
+        match rounded {
+            _ if rounded.is_nan() => f64::NAN,
+            // -0.0 and 0.0 are the same number, but not the same bits.
+            _ if rounded == 0.0 => 0.0,
+            _ => rounded,
+        }
+    }
+}
+
+fn digits_key(text : &str) -> NumberKey<'_> {
+    exact(text).unwrap_or(NumberKey::Text(text))
+}
+
+// The exact value of a number whose digits were kept.
+#[derive(PartialEq, Eq, Hash)]
+enum NumberKey<'a> {
+    // The value as digits * 10^exponent, with the zeros at either end of the
+    // digits moved into the exponent so every value has exactly one key. Zero
+    // has no digits and is never negative.
+    Exact { negative : bool, digits : String, exponent : i128 },
+    // Digits with an exponent too long to work with, only equal to themselves.
+    Text(&'a str),
+}
+
+// Reads a number written as -?digits(.digits)?([eE][+-]?digits)? into its
+// exact value.
+fn exact(text : &str) -> Option<NumberKey<'static>> {
+
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text),
+    };
+
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i128>().ok()?),
+        None => (unsigned, 0),
+    };
+
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    if !whole.bytes().chain(fraction.bytes()).all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits = format!("{}{}", whole, fraction);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+
+    if significant.is_empty() {
+        return Some(NumberKey::Exact { negative : false, digits : String::new(), exponent : 0 });
+    }
+
+    let exponent = exponent
+        .checked_sub(fraction.len() as i128)?
+        .checked_add((digits.len() - significant.len()) as i128)?;
+
+    Some(NumberKey::Exact { negative, digits : significant.to_string(), exponent })
+}
+
+// Numbers are equal when they have the same value, whatever their type or
+// however they were written: 1, 1.0 and 1e0 are all equal, while 2^53 + 1 and
+// the float it rounds to are not. Digits kept in arbitrary precision mode
+// compare exactly against each other, so 0.1 and 0.10000000000000001 differ
+// there. Against a float they compare by the shortest decimal the float
+// prints as, or by its exact value. Kept 0.1 therefore equals json!(0.1), and
+// a document parsed with and without the mode compares equal as long as its
+// numbers are written the way doubles print. Kept 0.10000000000000001 also
+// reads back as the float 0.1 but isn't equal to it, the one place equality
+// isn't transitive. NaN, which only From<f64> can produce, equals itself.
+impl PartialEq for Number {
+    fn eq(&self, other : &Number) -> bool {
+        match (self.as_str(), other.as_str()) {
+            (Some(text), Some(other_text)) => digits_key(text) == digits_key(other_text),
+            (Some(text), None) => other.matches_digits(text),
+            (None, Some(text)) => self.matches_digits(text),
+            (None, None) => match (self.value, other.value) {
+                (Num::Flt(flt), Num::Flt(other_flt)) => flt == other_flt || (flt.is_nan() && other_flt.is_nan()),
+                _ => matches!((self.integer(), other.integer()), (Some(int), Some(other_int)) if int == other_int),
+            },
+        }
+    }
+}
+
+impl Eq for Number {}
+
+impl Hash for Number {
+    fn hash<H : Hasher>(&self, state : &mut H) {
+        self.rounded().to_bits().hash(state);
+    }
+}
+
+impl From<bool> for Member {
+    fn from(value : bool) -> Member {
+        Member::Bool(value)
+    }
+}
+
+impl From<String> for Member {
+    fn from(value : String) -> Member {
+        Member::Str(value)
+    }
+}
+
+impl From<&str> for Member {
+    fn from(value : &str) -> Member {
+        Member::Str(value.to_string())
+    }
+}
+
+// Whole numbers are stored the way the parser would store them when read
+// back, so non-negative ones are always Num::Uint.
+macro_rules! from_integer {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Member {
+                fn from(value : $int) -> Member {
+                    let value = value as i128;
+
+                    match u64::try_from(value) {
+                        Ok(uint) => Member::Num(Number::of(Num::Uint(uint))),
+                        Err(_) => Member::Num(Number::of(Num::Int(value as i64))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl From<f64> for Member {
+    fn from(value : f64) -> Member {
+        Member::Num(Number::of(Num::Flt(value)))
+    }
+}
+
+impl From<f32> for Member {
+    fn from(value : f32) -> Member {
+        Member::from(value as f64)
+    }
+}
+
+impl From<JSON> for Member {
+    fn from(value : JSON) -> Member {
+        Member::JSON(value)
+    }
+}
+
+impl<T : Into<Member>> From<Option<T>> for Member {
+    fn from(value : Option<T>) -> Member {
+        match value {
+            Some(value) => value.into(),
+            None => Member::Null,
+        }
+    }
+}
+
+impl<T : Into<Member>> From<Vec<T>> for Member {
+    fn from(value : Vec<T>) -> Member {
+        value.into_iter().collect()
+    }
+}
+
+impl<K : Into<String>, V : Into<Member>> From<HashMap<K, V>> for Member {
+    fn from(value : HashMap<K, V>) -> Member {
+        Member::JSON(value.into_iter().collect())
+    }
+}
+
+impl<K : Into<String>, V : Into<Member>> From<BTreeMap<K, V>> for Member {
+    fn from(value : BTreeMap<K, V>) -> Member {
+        Member::JSON(value.into_iter().collect())
+    }
+}
+
+impl<T : Into<Member>> FromIterator<T> for Member {
+    fn from_iter<I : IntoIterator<Item = T>>(iter : I) -> Member {
+        Member::Arr(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K : Into<String>, V : Into<Member>> FromIterator<(K, V)> for JSON {
+    fn from_iter<I : IntoIterator<Item = (K, V)>>(iter : I) -> JSON {
+        let mut json = JSON::new();
+
+        for (key, value) in iter {
+            json.insert(key, value.into());
+        }
+
+        json
+    }
+}
+
+// Builds a Member from JSON written inline, with any Rust expression that
+// converts into a Member allowed wherever a value goes:
+//
+// json!({ "name" : name, "ports" : [80, port + 1], "backup" : null })
+//
+// Keys are expressions too, usually string literals.
+//
+// Each step of the expansion takes a run of up to eight array elements or four
+// object members written as single tokens, or one value spanning several
+// tokens like -1 or port + 1. The default recursion_limit of 128 steps covers
+// hundreds of single-token values, but only around a hundred longer ones in
+// one array or object. Wrapping those in parentheses makes them single tokens.
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+// A value can be any expression, so its end is only known from the ',' after
+// it. Runs of single-token values can be split on commas by matching alone,
+// and anything longer is parsed as an expression one value at a time.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // Array elements, collected into [$($elems,)*] until the tokens run out.
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] $($rest:tt),+ $(,)?) => {
+        vec![$($elems,)* $($crate::json_internal!($rest)),+]
+    };
+    (@array [$($elems:expr,)*] $a:tt, $b:tt, $c:tt, $d:tt, $e:tt, $f:tt, $g:tt, $h:tt, $($rest:tt)*) => {
+        $crate::json_internal!(@array [
+            $($elems,)*
+            $crate::json_internal!($a), $crate::json_internal!($b), $crate::json_internal!($c), $crate::json_internal!($d),
+            $crate::json_internal!($e), $crate::json_internal!($f), $crate::json_internal!($g), $crate::json_internal!($h),
+        ] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] null, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(null),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*], $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!([$($array)*]),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($map:tt)*}, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!({$($map)*}),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        vec![$($elems,)* $crate::json_internal!($last)]
+    };
+
+    // Object members, inserted into $object as they are split off. A key
+    // longer than one token is gathered into parentheses first.
+    (@object $object:ident ()) => {};
+    (@object $object:ident ($($key:tt : $value:tt),+ $(,)?)) => {
+        $(let _ = $object.insert(($key), $crate::json_internal!($value));)+
+    };
+    (@object $object:ident ($k1:tt : $v1:tt, $k2:tt : $v2:tt, $k3:tt : $v3:tt, $k4:tt : $v4:tt, $($rest:tt)*)) => {
+        let _ = $object.insert(($k1), $crate::json_internal!($v1));
+        let _ = $object.insert(($k2), $crate::json_internal!($v2));
+        let _ = $object.insert(($k3), $crate::json_internal!($v3));
+        let _ = $object.insert(($k4), $crate::json_internal!($v4));
+        $crate::json_internal!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:tt : null, $($rest:tt)*)) => {
+        let _ = $object.insert(($key), $crate::json_internal!(null));
+        $crate::json_internal!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:tt : [$($array:tt)*], $($rest:tt)*)) => {
+        let _ = $object.insert(($key), $crate::json_internal!([$($array)*]));
+        $crate::json_internal!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:tt : {$($map:tt)*}, $($rest:tt)*)) => {
+        let _ = $object.insert(($key), $crate::json_internal!({$($map)*}));
+        $crate::json_internal!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:tt : $value:expr, $($rest:tt)*)) => {
+        let _ = $object.insert(($key), $crate::json_internal!($value));
+        $crate::json_internal!(@object $object ($($rest)*));
+    };
+    (@object $object:ident ($key:tt : $value:expr)) => {
+        let _ = $object.insert(($key), $crate::json_internal!($value));
+    };
+    (@object $object:ident [$($key:tt)+] (: $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object (($($key)+) : $($rest)*));
+    };
+    (@object $object:ident [$($key:tt)*] ($tt:tt $($rest:tt)*)) => {
+        $crate::json_internal!(@object $object [$($key)* $tt] ($($rest)*));
+    };
+    (@object $object:ident ($($rest:tt)+)) => {
+        $crate::json_internal!(@object $object [] ($($rest)+));
+    };
+
+    (null) => {
+        $crate::Member::Null
+    };
+    (true) => {
+        $crate::Member::from(true)
+    };
+    (false) => {
+        $crate::Member::from(false)
+    };
+    ([]) => {
+        $crate::Member::Arr(vec![])
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Member::Arr($crate::json_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Member::JSON($crate::JSON::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::Member::JSON({
+            let mut object = $crate::JSON::new();
+            $crate::json_internal!(@object object ($($tt)+));
+            object
+        })
+    };
+    ($other:expr) => {
+        $crate::Member::from($other)
+    };
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key : &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn get(&self, key : &str) -> Option<&Member> {
+        let position = *self.index.get(key)?;
+
+        self.entries.get(position).map(|(_, member)| member)
+    }
+
+    pub fn get_mut(&mut self, key : &str) -> Option<&mut Member> {
+        let position = *self.index.get(key)?;
+
+        self.entries.get_mut(position).map(|(_, member)| member)
+    }
+
+    // A key that is already present keeps its place and gets the new value,
+    // handing back the old one.
+    pub fn insert(&mut self, key : String, member : Member) -> Option<Member> {
+        if let Some(existing) = self.get_mut(&key) {
+            return Some(mem::replace(existing, member));
+        }
+
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, member));
+
+        None
+    }
+
+    // Closes the gap so the remaining members keep their order.
+    pub fn remove(&mut self, key : &str) -> Option<Member> {
+        let position = self.index.remove(key)?;
+
+        let (_, member) = self.entries.remove(position);
+
+        for (_, later) in self.index.iter_mut() {
+            if *later > position {
+                *later -= 1;
+            }
+        }
+
+        Some(member)
+    }
+
+    pub fn entry(&mut self, key : impl Into<String>) -> Entry<'_> {
+        let key = key.into();
+
+        match self.index.get(&key) {
+            Some(&position) if position < self.entries.len() => {
+                Entry::Occupied(&mut self.entries[position].1)
+            }
+            _ => Entry::Vacant(self, key),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            entries : self.entries.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut Member)> {
+        self.entries.iter_mut().map(|(key, member)| (&*key, member))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Member> {
+        self.entries.iter().map(|(_, member)| member)
+    }
+}
+
+impl<'a> Entry<'a> {
+    pub fn or_insert(self, default : Member) -> &'a mut Member {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F : FnOnce() -> Member>(self, default : F) -> &'a mut Member {
+        match self {
+            Entry::Occupied(member) => member,
+            Entry::Vacant(map, key) => {
+                let position = map.entries.len();
+
+                map.index.insert(key.clone(), position);
+                map.entries.push((key, default()));
+
+                &mut map.entries[position].1
+            }
+        }
+    }
+
+    pub fn and_modify<F : FnOnce(&mut Member)>(self, modify : F) -> Entry<'a> {
+        match self {
+            Entry::Occupied(member) => {
+                modify(member);
+                Entry::Occupied(member)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Member);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|(key, member)| (key, member))
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a String, &'a Member);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+// Shown as the object it holds, leaving out the index.
+impl fmt::Debug for Map {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+// Objects with the same members are equal whatever order they came in.
+impl PartialEq for Map {
+    fn eq(&self, other : &Map) -> bool {
+        self.len() == other.len() && self.iter().all(|(key, member)| other.get(key) == Some(member))
+    }
+}
+
+impl Eq for Map {}
+
+// Each member is hashed on its own and the results summed, which doesn't
+// depend on the order they are added in.
+impl Hash for Map {
+    fn hash<H : Hasher>(&self, state : &mut H) {
+        let mut sum : u64 = 0;
+
+        for entry in self.iter() {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+        }
+
+        self.len().hash(state);
+        sum.hash(state);
+    }
+}
+
+impl Parser {
+    fn new(options : ParserOptions) -> Parser {
+        Parser {
+            options,
+            stack : vec![Frame::Root(None)],
+            decoder : Utf8Decoder::new(),
+            position : Position::start(),
+            pending : 0,
+        }
+    }
+
+    fn feed(&mut self, bytes : &[u8]) -> Result<(), ParseError> {
+
+        for curr_u8 in bytes {
+            let position = self.position;
+            let pending = self.pending;
+
+            let decoded = self.decoder.push(*curr_u8).map_err(|err| {
+                err.at(Position { offset : position.offset + pending, ..position })
+            })?;
+
+            match decoded {
+                Some(char) => {
+                    parse_character(char, position, &mut self.stack, &self.options).map_err(|err| err.at(position))?;
+                    self.position.advance(char);
+                    self.pending = 0;
+                }
+                None => {
+                    self.pending += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Member, ParseError> {
+
+        let position = self.position;
+        let at_end = |err : ParseError| err.at(position);
+
+        self.decoder.finish().map_err(at_end)?;
+
+        match self.stack.pop() {
+            Some(Frame::Object(_)) => Err(at_end(ParseError::eof("'}' to close the object"))),
+            Some(Frame::Array(_)) => Err(at_end(ParseError::eof("']' to close the array"))),
+            Some(frame) if frame.in_string() => Err(at_end(ParseError::eof("'\"' to close the string"))),
+            Some(Frame::Root(Some(partial))) => finish_value(partial, &self.options).map_err(at_end),
+            _ => Err(at_end(ParseError::eof("a JSON value"))),
+        }
+    }
+}
+
+impl ObjectState {
+    fn new() -> ObjectState {
+        ObjectState {
+            json : JSON::new(),
+            key : String::new(),
+            in_key : false,
+            key_escape : Escape::None,
+            key_start : Position::default(),
+            value : None,
+            expect : Expect::Start,
+        }
+    }
+
+    fn expected(&self) -> &'static str {
+        match self.expect {
+            Expect::Start => "an object key or '}'",
+            Expect::Key => "an object key",
+            Expect::Colon => "':' after object key",
+            Expect::Value if self.value.is_none() => "a value",
+            Expect::Value => "',' or '}' after object member",
+        }
+    }
+}
+
+impl ArrayState {
+    fn new() -> ArrayState {
+        ArrayState {
+            values : Vec::new(),
+            value : None,
+            expect : Expect::Start,
+        }
+    }
+}
+
+impl Frame {
+    fn expects_value(&self) -> bool {
+        match self {
+            Frame::Root(value) | Frame::Array(ArrayState { value, .. }) => {
+                value.is_none()
+            }
+            Frame::Object(state) => {
+                state.expect == Expect::Value && state.value.is_none()
+            }
+        }
+    }
+
+    // What could have come next, for errors about what came instead.
+    fn expected(&self) -> &'static str {
+        match self {
+            Frame::Root(None) => "a JSON value",
+            Frame::Root(Some(_)) => "the end of the input",
+            Frame::Object(state) => state.expected(),
+            Frame::Array(array) => {
+                match array.value {
+                    None if array.expect == Expect::Start => "a value or ']'",
+                    None => "a value",
+                    Some(_) => "',' or ']' after array element",
+                }
+            }
+        }
+    }
+
+    fn in_string(&self) -> bool {
+        match self {
+            Frame::Root(value) | Frame::Array(ArrayState { value, .. }) => {
+                matches!(value, Some(Partial::Str(..)))
+            }
+            Frame::Object(state) => {
+                state.in_key || matches!(state.value, Some(Partial::Str(..)))
+            }
+        }
+    }
+
+    fn set_value(&mut self, member : Member) {
+        match self {
+            Frame::Root(value) | Frame::Array(ArrayState { value, .. }) | Frame::Object(ObjectState { value, .. }) => {
+                *value = Some(Partial::Done(member));
+            }
+        }
+    }
+
+    fn close_object(self, options : &ParserOptions) -> Result<JSON, ParseError> {
+        match self {
+            Frame::Object(mut state) => {
+                match (state.expect, state.value.take()) {
+                    (Expect::Value, Some(partial)) => {
+                        let value = finish_value(partial, options)?;
+
+                        insert_member(&mut state, value, options);
+                    }
+                    (Expect::Start, _) => (),
+                    (Expect::Key, _) if !options.strict => (),
+                    (Expect::Key, _) => return Err(ParseError::unexpected('}', "an object key after ','")),
+                    (Expect::Colon, _) => return Err(ParseError::unexpected('}', "':' after object key")),
+                    (Expect::Value, None) => return Err(ParseError::unexpected('}', "a value after ':'")),
+                }
+
+                Ok(state.json)
+            }
+            _ => Err(ParseError::unexpected('}', "a value")),
+        }
+    }
+
+    fn close_array(self, options : &ParserOptions) -> Result<Vec<Member>, ParseError> {
+        match self {
+            Frame::Array(mut array) => {
+                match array.value {
+                    None => {
+                        if array.expect != Expect::Start && options.strict {
+                            return Err(ParseError::unexpected(']', "a value after ','"));
                         }
-                        Num::Flt(flt) => {
-                            if num.digit == 0 {
-                                num.value = Num::Flt(flt+((current_char.to_digit(10).expect("This  should be a number") as f32)/10.00)*sign as f32);
-                                num.digit += 1;
+                    }
+                    Some(partial) => {
+                        array.values.push(finish_value(partial, options)?);
+                    }
+                }
+
+                Ok(array.values)
+            }
+            _ => Err(ParseError::unexpected(']', "a value")),
+        }
+    }
+}
+
+// Writes compact JSON, so anything printed parses back to the same value.
+impl std::fmt::Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Member::Bool(booly) => {
+                write!(f,"{}",booly)
+            }
+            Member::Str(string) => {
+                write_string(f, string)
+            }
+            Member::Num(nummer) =>{
+                if let Some(text) = nummer.as_str() {
+                    return write!(f, "{}", text);
+                }
+
+                match nummer.value {
+                    Num::Uint(uint) => {
+                        write!(f, "{}", uint)
+                    }
+                    Num::Int(int) => {
+                        write!(f, "{}", int)
+                    }
+                    // Debug keeps the ".0" on whole floats and switches to an
+                    // exponent for very large or small ones, both valid JSON.
+                    Num::Flt(flt) if flt.is_finite() => {
+                        write!(f,"{:?}", flt)
+                    }
+                    _ =>{
+                        write!(f,"null")
+                    }
+                }
+            }
+            Member::Arr(array) => {
+                write!(f, "[")?;
+
+                for (index, member) in array.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", member)?;
+                }
+
+                write!(f, "]")
+            }
+            Member::JSON(json) => {
+                write!(f, "{}", json)
+            }
+            Member::Null => {
+                write!(f,"null")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for JSON {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+
+        for (index, (key, member)) in self.map.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+
+            write_string(f, key)?;
+            write!(f, ":{}", member)?;
+        }
+
+        write!(f, "}}")
+    }
+}
+
+impl Member {
+    pub fn to_string_pretty(&self) -> String {
+        self.to_string_pretty_with(PrettyOptions::default())
+    }
+
+    pub fn to_string_pretty_with(&self, options : PrettyOptions) -> String {
+        let mut pretty = String::new();
+
+        // Writing into a String can't fail.
+        let _ = write_pretty(&mut pretty, self, &options, 0);
+
+        if options.trailing_newline {
+            pretty.push('\n');
+        }
+
+        pretty
+    }
+}
+
+impl JSON {
+    pub fn to_string_pretty(&self) -> String {
+        self.to_string_pretty_with(PrettyOptions::default())
+    }
+
+    pub fn to_string_pretty_with(&self, options : PrettyOptions) -> String {
+        let mut pretty = String::new();
+
+        let _ = write_object(&mut pretty, self, &options, 0);
+
+        if options.trailing_newline {
+            pretty.push('\n');
+        }
+
+        pretty
+    }
+}
+
+impl Member {
+    // RFC 8785 canonical JSON: no whitespace, object keys sorted by their
+    // UTF-16 code units and every number written the way ECMAScript would
+    // write it as a double. Two documents with the same data always come out
+    // byte for byte the same, which is what signatures need.
+    pub fn to_canonical_string(&self) -> Result<String> {
+        let mut canonical = String::new();
+
+        write_canonical(&mut canonical, self)?;
+
+        Ok(canonical)
+    }
+}
+
+impl JSON {
+    pub fn to_canonical_string(&self) -> Result<String> {
+        let mut canonical = String::new();
+
+        write_canonical_object(&mut canonical, self)?;
+
+        Ok(canonical)
+    }
+}
+
+fn write_pretty(out : &mut String, member : &Member, options : &PrettyOptions, depth : usize) -> fmt::Result {
+
+    match member {
+        Member::Arr(array) if !array.is_empty() => {
+            out.push('[');
+
+            for (index, member) in array.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+
+                write_indent(out, options, depth + 1);
+                write_pretty(out, member, options, depth + 1)?;
+            }
+
+            write_indent(out, options, depth);
+            out.push(']');
+
+            Ok(())
+        }
+        Member::JSON(json) => {
+            write_object(out, json, options, depth)
+        }
+        // Scalars and empty arrays look the same either way.
+        _ => {
+            write!(out, "{}", member)
+        }
+    }
+}
+
+fn write_object(out : &mut String, json : &JSON, options : &PrettyOptions, depth : usize) -> fmt::Result {
+
+    if json.map.is_empty() {
+        out.push_str("{}");
+        return Ok(());
+    }
+
+    out.push('{');
+
+    for (index, (key, member)) in json.map.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+
+        write_indent(out, options, depth + 1);
+        write_string(out, key)?;
+        out.push(':');
+
+        if options.space_after_colon {
+            out.push(' ');
+        }
+
+        write_pretty(out, member, options, depth + 1)?;
+    }
+
+    write_indent(out, options, depth);
+    out.push('}');
+
+    Ok(())
+}
+
+fn write_canonical(out : &mut String, member : &Member) -> Result<()> {
+
+    match member {
+        Member::Num(nummer) => {
+            let flt = nummer.to_f64().ok_or_else(|| anyhow!("Number has no value to write"))?;
+
+            out.push_str(&canonical_number(flt)?);
+        }
+        Member::Arr(array) => {
+            out.push('[');
+
+            for (index, member) in array.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+
+                write_canonical(out, member)?;
+            }
+
+            out.push(']');
+        }
+        Member::JSON(json) => {
+            write_canonical_object(out, json)?;
+        }
+        // Strings and literals already come out minimally escaped.
+        _ => {
+            write!(out, "{}", member)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_canonical_object(out : &mut String, json : &JSON) -> Result<()> {
+
+    let mut members : Vec<(&String, &Member)> = json.map.iter().collect();
+
+    members.sort_by(|(first, _), (second, _)| first.encode_utf16().cmp(second.encode_utf16()));
+
+    out.push('{');
+
+    for (index, (key, member)) in members.into_iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+
+        write_string(out, key)?;
+        out.push(':');
+        write_canonical(out, member)?;
+    }
+
+    out.push('}');
+
+    Ok(())
+}
+
+// Number::prototype::toString from ECMA-262, which RFC 8785 uses for every
+// number. Rust's exponent formatting already finds the shortest digits that
+// read back as the same double, so only the layout around them is left.
+fn canonical_number(flt : f64) -> Result<String> {
+
+    if !flt.is_finite() {
+        return Err(anyhow!("{} can't be written as JSON", flt));
+    }
+
+    // Covers -0 as well.
+    if flt == 0.0 {
+        return Ok(String::from("0"));
+    }
+
+    let sign = if flt < 0.0 { "-" } else { "" };
+
+    let shortest = format!("{:e}", flt.abs());
+
+    // When several strings of the shortest length read back as the same
+    // double, ECMAScript wants the one closest to it, which the shortest
+    // formatting doesn't promise. Rounding to that many digits gives it.
+    let length = shortest.split('e').next().unwrap_or_default().replace('.', "").len();
+    let closest = format!("{:.*e}", length.saturating_sub(1), flt.abs());
+
+    let shortest = match closest.parse::<f64>() {
+        Ok(parsed) if parsed == flt.abs() => closest,
+        _ => shortest,
+    };
+
+    let (mantissa, exponent) = shortest.split_once('e').ok_or_else(|| anyhow!("Unexpected float format '{}'", shortest))?;
+
+    let digits : String = mantissa.chars().filter(|current_char| *current_char != '.').collect();
+    let exponent : i32 = exponent.parse()?;
+
+    // With k digits, the value is 0.digits times 10 to the n.
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let number = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    }
+    else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    }
+    else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    }
+    else {
+        let exponent_sign = if n - 1 < 0 { '-' } else { '+' };
+
+        if k == 1 {
+            format!("{}e{}{}", digits, exponent_sign, (n - 1).abs())
+        }
+        else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], exponent_sign, (n - 1).abs())
+        }
+    };
+
+    Ok(format!("{}{}", sign, number))
+}
+
+// Starts a new line indented for the given depth.
+fn write_indent(out : &mut String, options : &PrettyOptions, depth : usize) {
+
+    out.push('\n');
+
+    let (unit, width) = match options.indent {
+        Indent::Spaces(width) => (' ', width),
+        Indent::Tabs(width) => ('\t', width),
+    };
+
+    out.extend(std::iter::repeat_n(unit, width * depth));
+}
+
+// Quotes a string, escaping only what JSON requires: the quote, the backslash
+// and control characters.
+fn write_string<W : fmt::Write>(f : &mut W, string : &str) -> fmt::Result {
+
+    write!(f, "\"")?;
+
+    for current_char in string.chars() {
+        match current_char {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\u{8}' => write!(f, "\\b")?,
+            '\u{c}' => write!(f, "\\f")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '\u{0}'..='\u{1f}' => write!(f, "\\u{:04x}", current_char as u32)?,
+            _ => write!(f, "{}", current_char)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+impl Position {
+    fn start() -> Position {
+        Position {
+            line : 1,
+            column : 1,
+            offset : 0,
+        }
+    }
+
+    fn advance(&mut self, current_char : char) {
+        self.offset += current_char.len_utf8();
+
+        if current_char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        }
+        else {
+            self.column += 1;
+        }
+    }
+
+    // Steps back over an ASCII token that can't span lines.
+    fn back(self, len : usize) -> Position {
+        Position {
+            line : self.line,
+            column : self.column.saturating_sub(len).max(1),
+            offset : self.offset.saturating_sub(len),
+        }
+    }
+}
+
+impl ParseError {
+    fn unexpected(found : char, expected : &str) -> ParseError {
+        ParseError::UnexpectedCharacter { found, expected : expected.to_string(), position : Position::default() }
+    }
+
+    fn eof(expected : &str) -> ParseError {
+        ParseError::UnexpectedEof { expected : expected.to_string(), position : Position::default() }
+    }
+
+    fn escape(sequence : String) -> ParseError {
+        ParseError::InvalidEscape { sequence, position : Position::default() }
+    }
+
+    pub fn position(&self) -> Position {
+        match self {
+            ParseError::UnexpectedCharacter { position, .. } |
+            ParseError::UnexpectedEof { position, .. } |
+            ParseError::InvalidEscape { position, .. } |
+            ParseError::InvalidUtf8 { position, .. } |
+            ParseError::InvalidNumber { position, .. } |
+            ParseError::NumberOverflow { position, .. } |
+            ParseError::InvalidLiteral { position, .. } |
+            ParseError::ControlCharacter { position, .. } |
+            ParseError::NotAnObject { position } |
+            ParseError::DuplicateKey { position, .. } |
+            ParseError::TooDeep { position, .. } |
+            ParseError::Io { position, .. } => *position,
+        }
+    }
+
+    // Called by the read loop with the position of the character being parsed
+    // when the error came up. Numbers and literals are only checked once
+    // something ends them, so those point back to where they began instead.
+    fn at(mut self, at : Position) -> ParseError {
+        match &mut self {
+            ParseError::InvalidNumber { text, position } |
+            ParseError::NumberOverflow { text, position } |
+            ParseError::InvalidLiteral { found : text, position, .. } => {
+                *position = at.back(text.len());
+            }
+            ParseError::UnexpectedCharacter { position, .. } |
+            ParseError::UnexpectedEof { position, .. } |
+            ParseError::InvalidEscape { position, .. } |
+            ParseError::InvalidUtf8 { position, .. } |
+            ParseError::ControlCharacter { position, .. } |
+            ParseError::NotAnObject { position } |
+            ParseError::TooDeep { position, .. } |
+            ParseError::Io { position, .. } => {
+                *position = at;
+            }
+            // Raised on the key's closing quote but already pointing at its
+            // opening one.
+            ParseError::DuplicateKey { .. } => (),
+        }
+
+        self
+    }
+}
+
+impl ParseError {
+    // What went wrong, without where or what would have been right.
+    fn summary(&self) -> String {
+        match self {
+            ParseError::UnexpectedCharacter { found, .. } => {
+                format!("Unexpected {:?}", found)
+            }
+            ParseError::UnexpectedEof { .. } => {
+                String::from("Unexpected end of input")
+            }
+            ParseError::InvalidEscape { sequence, .. } => {
+                format!("Invalid escape sequence '{}'", sequence)
+            }
+            ParseError::InvalidUtf8 { byte, .. } => {
+                format!("Invalid UTF-8 byte {:#04x}", byte)
+            }
+            ParseError::InvalidNumber { text, .. } => {
+                format!("Invalid number '{}'", text)
+            }
+            ParseError::NumberOverflow { text, .. } => {
+                format!("Number '{}' is too large to represent", text)
+            }
+            ParseError::InvalidLiteral { found, expected, .. } => {
+                format!("Incomplete literal '{}', expected '{}'", found, expected)
+            }
+            ParseError::ControlCharacter { found, .. } => {
+                format!("Unescaped control character {:#04x} in string", *found as u32)
+            }
+            ParseError::NotAnObject { .. } => {
+                String::from("Expected the document to be a JSON object")
+            }
+            ParseError::DuplicateKey { key, .. } => {
+                format!("Duplicate key '{}'", key)
+            }
+            ParseError::TooDeep { max_depth, .. } => {
+                format!("Nesting is deeper than the limit of {}", max_depth)
+            }
+            ParseError::Io { error, .. } => {
+                format!("Failed to read input: {}", error)
+            }
+        }
+    }
+
+    // A short suggestion for fixing the input, shown under the snippet.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            ParseError::UnexpectedCharacter { expected, .. } |
+            ParseError::UnexpectedEof { expected, .. } => {
+                Some(format!("expected {}", expected))
+            }
+            ParseError::InvalidEscape { .. } => {
+                Some(String::from("valid escapes are \\\" \\\\ \\/ \\b \\f \\n \\r \\t and \\uXXXX"))
+            }
+            ParseError::InvalidUtf8 { .. } => {
+                Some(String::from("the input must be encoded as UTF-8"))
+            }
+            ParseError::InvalidNumber { .. } => {
+                Some(String::from("numbers look like 12, -0.5 or 1e10"))
+            }
+            ParseError::NumberOverflow { .. } => {
+                Some(String::from("enable arbitrary_precision to keep numbers this large"))
+            }
+            ParseError::InvalidLiteral { expected, .. } => {
+                Some(format!("did you mean '{}'?", expected))
+            }
+            ParseError::ControlCharacter { .. } => {
+                Some(String::from("escape it, for example as \\n, \\t or \\u0001"))
+            }
+            ParseError::NotAnObject { .. } => {
+                Some(String::from("wrap the document in '{' and '}'"))
+            }
+            ParseError::DuplicateKey { .. } => {
+                Some(String::from("each key may only appear once in an object"))
+            }
+            ParseError::TooDeep { .. } => {
+                Some(String::from("raise max_depth if documents this deep are expected"))
+            }
+            ParseError::Io { .. } => None,
+        }
+    }
+
+    // Shows the line of source the error points at with a caret under the
+    // offending column, like so:
+    //
+    // error: Unexpected '2'
+    //  --> line 3, column 7
+    //   |
+    // 3 |   "b" 2
+    //   |       ^
+    //   = hint: expected ':' after object key
+    pub fn render(&self, source : &str) -> String {
+
+        let position = self.position();
+
+        let line = source.lines().nth(position.line.saturating_sub(1)).unwrap_or("");
+
+        // Tabs are kept so the caret lines up however wide they are drawn.
+        let padding : String = line.chars()
+            .take(position.column.saturating_sub(1))
+            .map(|current_char| if current_char == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let number = position.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        let mut rendered = format!("error: {}\n", self.summary());
+
+        rendered.push_str(&format!("{}--> line {}, column {}\n", gutter, position.line, position.column));
+        rendered.push_str(&format!("{} |\n", gutter));
+        rendered.push_str(&format!("{} | {}\n", number, line));
+        rendered.push_str(&format!("{} | {}^", gutter, padding));
+
+        if let Some(hint) = self.hint() {
+            rendered.push_str(&format!("\n{} = hint: {}", gutter, hint));
+        }
+
+        rendered
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedCharacter { expected, .. } |
+            ParseError::UnexpectedEof { expected, .. } => {
+                write!(f, "{}, expected {}", self.summary(), expected)?;
+            }
+            _ => {
+                write!(f, "{}", self.summary())?;
+            }
+        }
+
+        let position = self.position();
+
+        write!(f, " at line {}, column {}", position.line, position.column)
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+
+pub fn get_reader() -> Result<BufReader<File>> {
+
+    let args : Vec<String> = env::args().collect();
+
+    let file_path = parse_args(&args)?;
+
+    read_file(file_path)
+}
+
+
+fn parse_args(input_args : &[String]) -> Result<&str> {
+
+    let file_path = match input_args.get(1) {
+        Some(file_path) => {
+            file_path
+        },
+        None => {
+            return Err(anyhow!("No JSON file path provided"));
+        }
+    };
+
+    match file_path.split('.').next_back() {
+        Some(file_type) => {
+            if file_type != "json" {
+                return Err(anyhow!("This is not a valid JSON file"));
+            }
+        }
+        None => {
+            return Err(anyhow!("There's an error in the file path provided"));
+        }
+    };
+
+
+
+    Ok(file_path)
+}
+
+fn read_file(file_path : &str) -> Result<BufReader<File>>  {
+
+    let file = fs::File::open(file_path)?;
+
+    let reader = io::BufReader::new(file);
+
+    Ok(reader)
+}
+
+pub fn parse_json(reader : &mut BufReader<File>) -> Result<JSON, ParseError> {
+
+    match parse_value(reader)? {
+        Member::JSON(json) => Ok(json),
+        _ => Err(ParseError::NotAnObject { position : Position::start() }),
+    }
+}
+
+pub fn parse_value(reader : &mut BufReader<File>) -> Result<Member, ParseError> {
+
+    from_reader(reader)
+}
+
+pub fn from_str(input : &str) -> Result<Member, ParseError> {
+
+    from_str_with(input, ParserOptions::default())
+}
+
+pub fn from_slice(input : &[u8]) -> Result<Member, ParseError> {
+
+    from_slice_with(input, ParserOptions::default())
+}
+
+pub fn from_reader<R : Read>(reader : R) -> Result<Member, ParseError> {
+
+    from_reader_with(reader, ParserOptions::default())
+}
+
+pub fn from_str_with(input : &str, options : ParserOptions) -> Result<Member, ParseError> {
+
+    from_slice_with(input.as_bytes(), options)
+}
+
+pub fn from_slice_with(input : &[u8], options : ParserOptions) -> Result<Member, ParseError> {
+
+    from_reader_with(input, options)
+}
+
+pub fn from_reader_with<R : Read>(mut reader : R, options : ParserOptions) -> Result<Member, ParseError> {
+
+    let mut parser = Parser::new(options);
+
+    let mut buffer : [u8;4096] = [0u8;4096];
+
+    loop {
+
+        let len = match reader.read(&mut buffer) {
+            Ok(len) => len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(ParseError::Io { error, position : parser.position }),
+        };
+
+        if len == 0 {
+            break
+        }
+
+        // A reader claiming more than it was given is broken, not the input.
+        let read = buffer.get(..len).ok_or_else(|| ParseError::Io {
+            error : io::Error::new(io::ErrorKind::InvalidData, "Reader returned more bytes than the buffer holds"),
+            position : parser.position,
+        })?;
+
+        parser.feed(read)?;
+    }
+
+    parser.finish()
+}
+
+fn parse_character(current_char : char, position : Position, stack : &mut Vec<Frame>, options : &ParserOptions) -> Result<(), ParseError> {
+
+    let frame = match stack.last_mut() {
+        Some(frame) => frame,
+        None => return Err(ParseError::unexpected(current_char, "the end of the input")),
+    };
+
+    if frame.in_string() {
+        return parse_string(current_char, frame, options);
+    }
+
+    match current_char {
+        '{' | '[' => {
+            if !frame.expects_value() {
+                return Err(ParseError::unexpected(current_char, frame.expected()));
+            }
+
+            // The Root frame doesn't count towards the depth.
+            if stack.len() > options.max_depth {
+                return Err(ParseError::TooDeep { max_depth : options.max_depth, position : Position::default() });
+            }
+
+            if current_char == '{' {
+                stack.push(Frame::Object(ObjectState::new()));
+            }
+            else {
+                stack.push(Frame::Array(ArrayState::new()));
+            }
+        }
+        '}' => {
+            match frame {
+                Frame::Object(_) => {
+                    let closed = stack.pop().ok_or_else(|| ParseError::unexpected('}', "a value"))?.close_object(options)?;
+
+                    if let Some(parent) = stack.last_mut() {
+                        parent.set_value(Member::JSON(closed));
+                    }
+                }
+                Frame::Root(_) => {
+                    return Err(ParseError::unexpected('}', frame.expected()));
+                }
+                Frame::Array(_) => {
+                    return Err(ParseError::unexpected('}', frame.expected()));
+                }
+            }
+        }
+        ']' => {
+            match frame {
+                Frame::Array(_) => {
+                    let closed = stack.pop().ok_or_else(|| ParseError::unexpected(']', "a value"))?.close_array(options)?;
+
+                    if let Some(parent) = stack.last_mut() {
+                        parent.set_value(Member::Arr(closed));
+                    }
+                }
+                Frame::Root(_) => {
+                    return Err(ParseError::unexpected(']', frame.expected()));
+                }
+                Frame::Object(_) => {
+                    return Err(ParseError::unexpected(']', frame.expected()));
+                }
+            }
+        }
+        _ => {
+            match frame {
+                Frame::Root(value) => {
+                    parse_root(current_char, value, options)?;
+                }
+                Frame::Object(state) => {
+                    parse_pair(current_char, position, state, options)?;
+                }
+                Frame::Array(array) => {
+                    parse_element(current_char, array, options)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Between the quotes of a key or string value every character is content, so
+// none of the structural characters get a say until the closing quote.
+fn parse_string(current_char : char, frame : &mut Frame, options : &ParserOptions) -> Result<(), ParseError> {
+
+    let closing = current_char == '"';
+
+    if options.strict && current_char < ' ' {
+        return Err(ParseError::ControlCharacter { found : current_char, position : Position::default() });
+    }
+
+    let value = match frame {
+        Frame::Object(state) if state.in_key => {
+            if !closing || state.key_escape.in_progress() {
+                state.key_escape = append_character(current_char, &mut state.key, state.key_escape)?;
+                return Ok(());
+            }
+
+            // A repeated key is caught as soon as it closes, so the error
+            // points at the key rather than somewhere after its value.
+            if options.duplicate_keys == DuplicateKeys::Error && state.json.map.contains_key(&state.key) {
+                return Err(ParseError::DuplicateKey { key : state.key.clone(), position : state.key_start });
+            }
+
+            state.in_key = false;
+            state.expect = Expect::Colon;
+            return Ok(());
+        }
+        Frame::Root(value) | Frame::Object(ObjectState { value, .. }) | Frame::Array(ArrayState { value, .. }) => value,
+    };
+
+    if let Some(Partial::Str(str, escape)) = value {
+        if closing && !escape.in_progress() {
+            *value = Some(Partial::Done(Member::Str(mem::take(str))));
+        }
+        else {
+            *escape = append_character(current_char, str, *escape)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_root(current_char : char, value : &mut Option<Partial>, options : &ParserOptions) -> Result<(), ParseError> {
+
+    let whitespace = is_whitespace(current_char, options);
+
+    match value.take() {
+        Some(Partial::Done(member)) => {
+            *value = Some(Partial::Done(member));
+
+            if whitespace {
+                return Ok(());
+            }
+
+            Err(ParseError::unexpected(current_char, "the end of the input"))
+        }
+        None if whitespace => {
+            Ok(())
+        }
+        None if matches!(current_char, ',' | ':') => {
+            Err(ParseError::unexpected(current_char, "a JSON value"))
+        }
+        None => {
+            *value = Some(create_value(current_char)?);
+            Ok(())
+        }
+        Some(partial) => {
+            *value = Some(fill_value(current_char, partial, options)?);
+            Ok(())
+        }
+    }
+}
+
+fn parse_pair(current_char : char, position : Position, state : &mut ObjectState, options : &ParserOptions) -> Result<(), ParseError> {
+
+    match current_char {
+        '"' => {
+            match state.expect {
+                Expect::Start | Expect::Key => {
+                    state.in_key = true;
+                    state.key_start = position;
+                }
+                Expect::Value if state.value.is_none() => {
+                    state.value = Some(Partial::Str(String::new(), Escape::None));
+                }
+                Expect::Colon => {
+                    return Err(ParseError::unexpected('"', "':' after object key"));
+                }
+                Expect::Value => {
+                    return Err(ParseError::unexpected('"', "',' or '}' after object member"));
+                }
+            }
+        }
+        ':' => {
+            if state.expect != Expect::Colon {
+                return Err(ParseError::unexpected(':', state.expected()));
+            }
+
+            state.expect = Expect::Value;
+        }
+        ',' => {
+            let partial = match state.value.take() {
+                Some(partial) if state.expect == Expect::Value => partial,
+                _ => return Err(ParseError::unexpected(',', state.expected())),
+            };
+
+            let value = finish_value(partial, options)?;
+
+            insert_member(state, value, options);
+
+            state.expect = Expect::Key;
+        }
+        _ => {
+            let whitespace = is_whitespace(current_char, options);
+
+            match state.value.take() {
+                None if whitespace => (),
+                Some(Partial::Done(member)) if whitespace => {
+                    state.value = Some(Partial::Done(member));
+                }
+                None if state.expect == Expect::Value => {
+                    state.value = Some(create_value(current_char)?);
+                }
+                Some(Partial::Done(member)) => {
+                    state.value = Some(Partial::Done(member));
+                    return Err(ParseError::unexpected(current_char, state.expected()));
+                }
+                Some(partial) => {
+                    state.value = Some(fill_value(current_char, partial, options)?);
+                }
+                None => {
+                    return Err(ParseError::unexpected(current_char, state.expected()));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_element(current_char : char, array : &mut ArrayState, options : &ParserOptions) -> Result<(), ParseError> {
+
+    match current_char {
+        '"' => {
+            if array.value.is_some() {
+                return Err(ParseError::unexpected('"', "',' or ']' after array element"));
+            }
+
+            array.value = Some(Partial::Str(String::new(), Escape::None));
+        }
+        ',' => {
+            let partial = array.value.take().ok_or_else(|| ParseError::unexpected(',', "a value"))?;
+
+            array.values.push(finish_value(partial, options)?);
+            array.expect = Expect::Value;
+        }
+        _ => {
+            let whitespace = is_whitespace(current_char, options);
+
+            match array.value.take() {
+                None if whitespace => (),
+                Some(Partial::Done(member)) if whitespace => {
+                    array.value = Some(Partial::Done(member));
+                }
+                None => {
+                    array.value = Some(create_value(current_char)?);
+                }
+                Some(Partial::Done(_)) => {
+                    return Err(ParseError::unexpected(current_char, "',' or ']' after array element"));
+                }
+                Some(partial) => {
+                    array.value = Some(fill_value(current_char, partial, options)?);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_whitespace(current_char : char, options : &ParserOptions) -> bool {
+
+    if options.strict {
+        return matches!(current_char, ' ' | '\t' | '\n' | '\r');
+    }
+
+    current_char.is_ascii_whitespace()
+}
+
+fn create_value(current_char : char) -> Result<Partial, ParseError> {
+
+    let new_data = match current_char {
+        // Literals are spelled out one letter at a time and only become a
+        // Member once the last one has arrived.
+        't' => Partial::Literal("true", 1),
+        'f' => Partial::Literal("false", 1),
+        'n' => Partial::Literal("null", 1),
+        '"' => Partial::Str(String::new(), Escape::None),
+        '0'..='9' | '-' => Partial::Num(String::from(current_char)),
+        _ => return Err(ParseError::unexpected(current_char, "a value")),
+    };
+
+    Ok(new_data)
+}
+
+// Feeds one more character outside of any string to a value still being
+// built. Whitespace ends numbers and literals, which is the only way they can
+// finish without a delimiter.
+fn fill_value(current_char : char, partial : Partial, options : &ParserOptions) -> Result<Partial, ParseError> {
+
+    if is_whitespace(current_char, options) {
+        return match partial {
+            Partial::Num(_) | Partial::Literal(..) => Ok(Partial::Done(finish_value(partial, options)?)),
+            _ => Ok(partial),
+        };
+    }
+
+    match partial {
+        Partial::Num(mut text) => {
+            match current_char {
+                '0'..='9' | '.' | 'e' | 'E' | '+' | '-' => {
+                    text.push(current_char);
+                }
+                _ => {
+                    return Err(ParseError::unexpected(current_char, "a digit, ',' or a closing bracket"));
+                }
+            }
+
+            Ok(Partial::Num(text))
+        }
+        Partial::Literal(correct, index) => {
+            // Complete literals are Done, so there is always a letter left.
+            let correct_char = correct[index..].chars().next().unwrap_or_default();
+
+            if current_char != correct_char {
+                let expected = format!("'{}' to spell '{}'", correct_char, correct);
+
+                return Err(ParseError::UnexpectedCharacter { found : current_char, expected, position : Position::default() });
+            }
+
+            if index + 1 == correct.len() {
+                return Ok(Partial::Done(literal(correct)));
+            }
+
+            Ok(Partial::Literal(correct, index + 1))
+        }
+        _ => {
+            Err(ParseError::unexpected(current_char, "',' or a closing bracket after the value"))
+        }
+    }
+}
+
+fn insert_member(state : &mut ObjectState, value : Member, options : &ParserOptions) {
+
+    let key = mem::take(&mut state.key);
+    let json = &mut state.json;
+
+    match options.duplicate_keys {
+        // Repeats under Error never get this far.
+        DuplicateKeys::KeepLast | DuplicateKeys::Error => {
+            json.map.insert(key, value);
+        }
+        DuplicateKeys::KeepFirst => {
+            if !json.map.contains_key(&key) {
+                json.map.insert(key, value);
+            }
+        }
+        DuplicateKeys::Collect => {
+            match json.map.get_mut(&key) {
+                // Every value under Collect is an array made here.
+                Some(Member::Arr(values)) => {
+                    values.push(value);
+                }
+                _ => {
+                    json.map.insert(key, Member::Arr(vec![value]));
+                }
+            }
+        }
+    }
+}
+
+fn literal(correct : &str) -> Member {
+
+    match correct {
+        "true" => Member::Bool(true),
+        "false" => Member::Bool(false),
+        _ => Member::Null,
+    }
+}
+
+// Turns a value that was still being built into its final form. Numbers can't
+// know they are done until something follows them, and a literal cut short
+// like "tru" is only caught here.
+fn finish_value(partial : Partial, options : &ParserOptions) -> Result<Member, ParseError> {
+
+    match partial {
+        Partial::Num(text) => {
+            let value = parse_number(&text, options)?;
+
+            // The digits are only worth keeping when they may say more than
+            // the parsed value does.
+            let text = if options.arbitrary_precision { text } else { String::new() };
+
+            Ok(Member::Num(Number { value, text }))
+        }
+        Partial::Literal(correct, index) if index < correct.len() => {
+            Err(ParseError::InvalidLiteral { found : correct[..index].to_string(), expected : correct.to_string(), position : Position::default() })
+        }
+        Partial::Literal(correct, _) => Ok(literal(correct)),
+        Partial::Str(..) => Err(ParseError::eof("'\"' to close the string")),
+        Partial::Done(member) => Ok(member),
+    }
+}
+
+fn parse_number(text : &str, options : &ParserOptions) -> Result<Num, ParseError> {
+
+    let invalid = || ParseError::InvalidNumber { text : text.to_string(), position : Position::default() };
+
+    if options.strict && !is_strict_number(text) {
+        return Err(invalid());
+    }
+
+    let is_float = text.contains(['.', 'e', 'E']);
+
+    if is_float {
+        let flt : f64 = text.parse().map_err(|_| invalid())?;
+
+        if flt.is_infinite() {
+            if options.arbitrary_precision {
+                return Ok(Num::Big);
+            }
+
+            return Err(ParseError::NumberOverflow { text : text.to_string(), position : Position::default() });
+        }
+
+        return Ok(Num::Flt(flt));
+    }
+
+    if text.starts_with('-') {
+        match text.parse::<i64>() {
+            Ok(int) => Ok(Num::Int(int)),
+            Err(err) if matches!(err.kind(), IntErrorKind::NegOverflow) => {
+                overflowed(text, options)
+            }
+            Err(_) => Err(invalid()),
+        }
+    }
+    else {
+        match text.parse::<u64>() {
+            Ok(uint) => Ok(Num::Uint(uint)),
+            Err(err) if matches!(err.kind(), IntErrorKind::PosOverflow) => {
+                overflowed(text, options)
+            }
+            Err(_) => Err(invalid()),
+        }
+    }
+}
+
+// The number grammar from RFC 8259: -? ( 0 | [1-9][0-9]* ) ( . [0-9]+ )? ( [eE] [+-]? [0-9]+ )?
+fn is_strict_number(text : &str) -> bool {
+
+    let bytes = text.as_bytes();
+    let mut index = 0;
+
+    let digits = |index : &mut usize| -> bool {
+        let start = *index;
+
+        while matches!(bytes.get(*index), Some(b'0'..=b'9')) {
+            *index += 1;
+        }
+
+        *index > start
+    };
+
+    if bytes.get(index) == Some(&b'-') {
+        index += 1;
+    }
+
+    match bytes.get(index) {
+        Some(b'0') => index += 1,
+        Some(b'1'..=b'9') => {
+            digits(&mut index);
+        }
+        _ => return false,
+    }
+
+    if bytes.get(index) == Some(&b'.') {
+        index += 1;
+
+        if !digits(&mut index) {
+            return false;
+        }
+    }
+
+    if matches!(bytes.get(index), Some(b'e' | b'E')) {
+        index += 1;
+
+        if matches!(bytes.get(index), Some(b'+' | b'-')) {
+            index += 1;
+        }
+
+        if !digits(&mut index) {
+            return false;
+        }
+    }
+
+    index == bytes.len()
+}
+
+fn overflowed(text : &str, options : &ParserOptions) -> Result<Num, ParseError> {
+
+    if options.arbitrary_precision {
+        return Ok(Num::Big);
+    }
+
+    Err(ParseError::NumberOverflow { text : text.to_string(), position : Position::default() })
+}
+
+fn append_character(current_char : char, stringer : &mut String, escape : Escape) -> Result<Escape, ParseError>{
+
+    match escape {
+        Escape::None => {
+            if current_char == '\\' {
+                return Ok(Escape::Backslash { high : None });
+            }
+
+            stringer.push(current_char);
+        }
+        Escape::Backslash { high : None } => {
+            match current_char {
+                '"' => stringer.push('"'),
+                '\\' => stringer.push('\\'),
+                '/' => stringer.push('/'),
+                'b' => stringer.push('\u{8}'),
+                'f' => stringer.push('\u{c}'),
+                'n' => stringer.push('\n'),
+                'r' => stringer.push('\r'),
+                't' => stringer.push('\t'),
+                'u' => {
+                    return Ok(Escape::Unicode { code : 0, digits : 0, high : None });
+                }
+                _ => {
+                    return Err(ParseError::escape(format!("\\{}", current_char)));
+                }
+            }
+        }
+        Escape::Backslash { high : Some(high) } => {
+            if current_char != 'u' {
+                return Err(ParseError::escape(format!("\\u{:04X}\\{}", high, current_char)));
+            }
+
+            return Ok(Escape::Unicode { code : 0, digits : 0, high : Some(high) });
+        }
+        Escape::LowSurrogate(high) => {
+            if current_char != '\\' {
+                return Err(ParseError::escape(format!("\\u{:04X}", high)));
+            }
+
+            return Ok(Escape::Backslash { high : Some(high) });
+        }
+        Escape::Unicode { code, digits, high } => {
+            let digit = current_char.to_digit(16).ok_or_else(|| {
+                let written = if digits == 0 { String::new() } else { format!("{:0width$X}", code, width = digits as usize) };
+
+                ParseError::escape(format!("\\u{}{}", written, current_char))
+            })?;
+
+            let code = code * 16 + digit;
+
+            if digits < 3 {
+                return Ok(Escape::Unicode { code, digits : digits + 1, high });
+            }
+
+            let scalar = match (high, code) {
+                (None, 0xD800..=0xDBFF) => {
+                    return Ok(Escape::LowSurrogate(code));
+                }
+                (None, 0xDC00..=0xDFFF) => {
+                    return Err(ParseError::escape(format!("\\u{:04X}", code)));
+                }
+                (None, _) => code,
+                (Some(high), 0xDC00..=0xDFFF) => {
+                    0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00)
+                }
+                (Some(high), _) => {
+                    return Err(ParseError::escape(format!("\\u{:04X}\\u{:04X}", high, code)));
+                }
+            };
+
+            let decoded = char::from_u32(scalar)
+                .ok_or_else(|| ParseError::escape(format!("\\u{:04X}", scalar)))?;
+
+            stringer.push(decoded);
+        }
+    }
+
+    Ok(Escape::None)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn passed_file(file_path : &str) -> Result<BufReader<File>>{
+    
+        let reader = read_file(file_path)?;
+        
+        Ok(reader)
+    }
+
+    #[test]
+    fn correct_file_path() {
+        let args = vec!["".to_string(), "file.json".to_string()];
+        
+        assert_eq!(parse_args(&args).ok(), Some("file.json"));
+    }
+
+
+    #[test]
+    fn no_file_path() {
+        let args = vec!["".to_string()];
+
+        assert!(parse_args(&args).is_err());
+    }
+
+    #[test]
+    fn no_file_type() {
+        let args = vec!["this.".to_string()];
+
+        assert!(parse_args(&args).is_err());
+    }
+
+    #[test]
+    fn no_json() -> Result<()> {
+        let fake_file = "       ";
+
+        assert!(from_str(fake_file).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn json() -> Result<()> {
+        
+        let file_path = "./json/empty.json"; 
+        let mut reader = passed_file(file_path)?;
+
+        let json = parse_json(&mut reader)?;
+
+        assert!(json.map.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn bool_json() -> Result<()> {
+        let file_path = "./json/bool.json";
+ 
+        let mut reader = passed_file(file_path)?;
+
+        let json = parse_json(&mut reader)?;
+
+        let field = json.map.get("bool").unwrap_or(&Member::Null);
+
+       match field {
+            Member::Bool(bool)=>{
+                assert!(*bool);
+            }
+            _=> { panic!("Not a boolean");}
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn uint_json() -> Result<()> {
+        let file_path: &str = "./json/number.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("uinter").unwrap_or( &Member::Null);
+
+        let value :Num = match field {
+            Member::Num(num)=>{
+                num.value
+            }
+            _ => return Err(anyhow!("No number found")),
+        };
+
+        match value {
+            Num::Uint(uint) => {
+                assert!(uint == 22);
+            }
+            _ => {
+                panic!("Wrong number type")
+            }
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn int_json() -> Result<()> {
+        let file_path: &str = "./json/number.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("inter").unwrap_or( &Member::Null);
+
+        let value :Num = match field {
+            Member::Num(num)=>{
+                num.value
+            }
+            _ => return Err(anyhow!("No number found")),
+        };
+
+        match value {
+            Num::Int(int) => {
+                assert!(int == -22);
+            }
+            _ => {
+                panic!("Wrong number type")
+            }
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn flt_json() -> Result<()> {
+        let file_path: &str = "./json/number.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("floater").unwrap_or( &Member::Null);
+
+        let value :Num = match field {
+            Member::Num(num)=>{
+                num.value
+            }
+            _ => return Err(anyhow!("No number found")),
+        };
+
+        match value {
+            Num::Flt(flt) => {
+                assert!(flt == -22.1);
+            }
+            _ => {
+                panic!("Wrong number type")
+            }
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn string_json() -> Result<()> {
+        let file_path: &str = "./json/string.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("stringer").unwrap_or( &Member::Null);
+
+        let value : String = match field {
+            Member::Str(str)=>{
+                str.to_string()
+            }
+            _ => {String::from("")}
+        };
+
+        assert!(value == "hello");
+
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_quote_json() -> Result<()> {
+        let file_path: &str = "./json/escaped_string.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("stringer").unwrap_or( &Member::Null);
+
+        let value : String = match field {
+            Member::Str(str)=>{
+                str.to_string()
+            }
+            _ => {String::from("")}
+        };
+
+        assert!(value == "hello\"");
+
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_slash_json() -> Result<()> {
+        let file_path: &str = "./json/escaped_string.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("slasher").unwrap_or( &Member::Null);
+
+        let value : String = match field {
+            Member::Str(str)=>{
+                str.to_string()
+            }
+            _ => {String::from("")}
+        };
+
+        assert!(value == "hello\\");
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_array_json() -> Result<()> {
+        let file_path: &str = "./json/empty_array.json";
+
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("arr").unwrap_or( &Member::Null);
+
+        match field {
+            Member::Arr(arr)=>{
+                assert!(arr.is_empty());
+            }
+            _=>{
+               return Err(anyhow!("No array found")); 
+            }
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn array_json() -> Result<()> {
+        let file_path: &str = "./json/array.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let field: &Member = json.map.get("arr").unwrap_or( &Member::Null);
+
+        match field {
+            Member::Arr(arr)=>{
+                if let Some(Member::Num(num)) = arr.first() {
+                    if let Num::Uint(uint) = num.value {
+                        assert!(uint == 1);
+                    }
+                }
+
+                if let Some(Member::Num(num)) = arr.get(1) {
+                    if let Num::Int(int) = num.value {
+                        assert!(int == -1);
+                    }
+                }
+
+                if let Some(Member::Num(num)) = arr.get(2) {
+                    if let Num::Flt(fltr) = num.value {
+                        assert!(fltr == 22.0);
+                    }
+                }
+
+                if let Some(Member::Str(str)) = arr.get(3) {
+                    assert!(str == "jimmy");
+                }
+
+                if let Some(Member::Bool(booly)) = arr.get(4) {
+                    assert!(*booly);
+                }
+            }
+            _=>{
+               return Err(anyhow!("No array found")); 
+            }
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_json() -> Result<()> {
+        let file_path: &str = "./json/nested.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let outer = match json.map.get("outer") {
+            Some(Member::JSON(outer)) => outer,
+            _ => return Err(anyhow!("No nested object found")),
+        };
+
+        let inner = match outer.map.get("inner") {
+            Some(Member::JSON(inner)) => inner,
+            _ => return Err(anyhow!("No doubly nested object found")),
+        };
+
+        match inner.map.get("deep") {
+            Some(Member::Num(num)) => {
+                if let Num::Uint(uint) = num.value {
+                    assert!(uint == 1);
+                }
+                else {
+                    panic!("Wrong number type");
+                }
+            }
+            _ => return Err(anyhow!("No number in nested object")),
+        };
+
+        match outer.map.get("name") {
+            Some(Member::Str(str)) => assert!(str == "nested"),
+            _ => return Err(anyhow!("No string in nested object")),
+        };
+
+        match json.map.get("after") {
+            Some(Member::Bool(booly)) => assert!(*booly),
+            _ => return Err(anyhow!("Member after nested object lost")),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_array_json() -> Result<()> {
+        let file_path: &str = "./json/nested_array.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let matrix = match json.map.get("matrix") {
+            Some(Member::Arr(matrix)) => matrix,
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        assert!(matrix.len() == 3);
+
+        let lengths : Vec<usize> = matrix.iter().map(|row| match row {
+            Member::Arr(row) => row.len(),
+            _ => usize::MAX,
+        }).collect();
+
+        assert!(lengths == vec![2, 1, 0]);
+
+        if let Member::Arr(row) = &matrix[0] {
+            if let Some(Member::Num(num)) = row.get(1) {
+                if let Num::Uint(uint) = num.value {
+                    assert!(uint == 2);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn array_of_objects_json() -> Result<()> {
+        let file_path: &str = "./json/nested_array.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let records = match json.map.get("records") {
+            Some(Member::Arr(records)) => records,
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        assert!(records.len() == 2);
+
+        for (index, record) in records.iter().enumerate() {
+            match record {
+                Member::JSON(object) => {
+                    match object.map.get("id") {
+                        Some(Member::Num(num)) => {
+                            if let Num::Uint(uint) = num.value {
+                                assert!(uint as usize == index + 1);
                             }
                             else {
-                                num.value = Num::Flt(flt + ((current_char.to_digit(10).expect("This should be a number") as f32)/(base.pow(num.digit) as f32))*sign as f32);
-                                num.digit += 1;
+                                panic!("Wrong number type");
                             }
                         }
-                        _ => ()
-                    };
+                        _ => return Err(anyhow!("No id in record")),
+                    }
+                }
+                _ => return Err(anyhow!("Record is not an object")),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_nesting_json() -> Result<()> {
+        let file_path: &str = "./json/nested_array.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        let mixed = match json.map.get("mixed") {
+            Some(Member::Arr(mixed)) => mixed,
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        assert!(mixed.len() == 3);
+
+        let inner = match &mixed[1] {
+            Member::Arr(inner) => inner,
+            _ => return Err(anyhow!("No nested array found")),
+        };
+
+        match inner.first() {
+            Some(Member::Bool(booly)) => assert!(*booly),
+            _ => return Err(anyhow!("No boolean in nested array")),
+        };
+
+        match inner.get(1) {
+            Some(Member::JSON(object)) => {
+                match object.map.get("inner") {
+                    Some(Member::Arr(deepest)) => assert!(deepest.len() == 1),
+                    _ => return Err(anyhow!("No array inside nested object")),
+                }
+            }
+            _ => return Err(anyhow!("No object in nested array")),
+        };
+
+        match &mixed[2] {
+            Member::JSON(object) => assert!(object.map.is_empty()),
+            _ => return Err(anyhow!("No empty object found")),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_array_json() -> Result<()> {
+        let file_path: &str = "./json/top_level_array.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let value: Member = parse_value(&mut reader)?;
+
+        match value {
+            Member::Arr(arr) => {
+                assert!(arr.len() == 2);
+                assert!(arr.iter().all(|record| matches!(record, Member::JSON(_))));
+            }
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_scalar_json() -> Result<()> {
+        let mut reader: BufReader<File> = passed_file("./json/top_level_string.json")?;
+
+        match parse_value(&mut reader)? {
+            Member::Str(str) => assert!(str == "hello"),
+            _ => return Err(anyhow!("No string found")),
+        };
+
+        let mut reader: BufReader<File> = passed_file("./json/top_level_number.json")?;
+
+        match parse_value(&mut reader)? {
+            Member::Num(num) => {
+                if let Num::Int(int) = num.value {
+                    assert!(int == -22);
+                }
+                else {
+                    panic!("Wrong number type");
+                }
+            }
+            _ => return Err(anyhow!("No number found")),
+        };
+
+        let mut reader: BufReader<File> = passed_file("./json/top_level_bool.json")?;
+
+        match parse_value(&mut reader)? {
+            Member::Bool(booly) => assert!(booly),
+            _ => return Err(anyhow!("No boolean found")),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_array_is_not_object() -> Result<()> {
+        let mut reader: BufReader<File> = passed_file("./json/top_level_array.json")?;
+
+        assert!(parse_json(&mut reader).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn null_json() -> Result<()> {
+        let file_path: &str = "./json/null.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        assert!(matches!(json.map.get("nothing"), Some(Member::Null)));
+        assert!(matches!(json.map.get("something"), Some(Member::Num(_))));
+
+        match json.map.get("list") {
+            Some(Member::Arr(arr)) => {
+                assert!(arr.len() == 3);
+                assert!(matches!(arr[0], Member::Null));
+                assert!(matches!(arr[1], Member::Bool(_)));
+                assert!(matches!(arr[2], Member::Null));
+            }
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        let mut reader: BufReader<File> = passed_file("./json/top_level_null.json")?;
+
+        let value: Member = parse_value(&mut reader)?;
+
+        assert!(matches!(value, Member::Null));
+        assert!(value.to_string() == "null");
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_memory_json() -> Result<()> {
+        let value: Member = from_str(r#"{"stringer" : "hello", "arr" : [1, 2]}"#)?;
+
+        match value {
+            Member::JSON(json) => {
+                assert!(matches!(json.map.get("stringer"), Some(Member::Str(str)) if str == "hello"));
+                assert!(matches!(json.map.get("arr"), Some(Member::Arr(arr)) if arr.len() == 2));
+            }
+            _ => return Err(anyhow!("No JSON found")),
+        };
+
+        let value: Member = from_slice(b"[true, false]")?;
+
+        assert!(matches!(value, Member::Arr(arr) if arr.len() == 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn reader_json() -> Result<()> {
+        let cursor = io::Cursor::new(String::from(r#"{"inter" : -22}"#));
+
+        match from_reader(cursor)? {
+            Member::JSON(json) => {
+                assert!(matches!(json.map.get("inter"), Some(Member::Num(_))));
+            }
+            _ => return Err(anyhow!("No JSON found")),
+        };
+
+        let file = fs::File::open("./json/bool.json")?;
+
+        assert!(matches!(from_reader(file)?, Member::JSON(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn utf8_json() -> Result<()> {
+        let file_path: &str = "./json/utf8.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        assert!(matches!(json.map.get("cafe"), Some(Member::Str(str)) if str == "café"));
+        assert!(matches!(json.map.get("ключ"), Some(Member::Str(str)) if str == "значение"));
+
+        match json.map.get("emoji") {
+            Some(Member::Arr(arr)) => {
+                assert!(matches!(&arr[0], Member::Str(str) if str == "🦀"));
+                assert!(matches!(&arr[1], Member::Str(str) if str == "日本語"));
+            }
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_utf8_json() {
+        assert!(from_slice(b"{\"bad\" : \"\xff\"}").is_err());
+        assert!(from_slice(b"{\"bad\" : \"\xc3\x28\"}").is_err());
+        assert!(from_slice(b"{\"overlong\" : \"\xe0\x80\xaf\"}").is_err());
+        assert!(from_slice(b"\"\xe6\x97").is_err());
+    }
+
+    #[test]
+    fn unicode_escape_json() -> Result<()> {
+        let file_path: &str = "./json/unicode_escape.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        assert!(matches!(json.map.get("accent"), Some(Member::Str(str)) if str == "café"));
+        assert!(matches!(json.map.get("crab"), Some(Member::Str(str)) if str == "🦀"));
+        assert!(matches!(json.map.get("controls"), Some(Member::Str(str)) if str == "\u{8}\u{c}/\n"));
+        assert!(matches!(json.map.get("upper"), Some(Member::Str(str)) if str == "Été"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_escape_json() {
+        assert!(from_str(r#"{"bad" : "\x41"}"#).is_err());
+        assert!(from_str(r#"{"bad" : "\uD800"}"#).is_err());
+        assert!(from_str(r#"{"bad" : "\uD800A"}"#).is_err());
+        assert!(from_str(r#"{"bad" : "\uDC00"}"#).is_err());
+        assert!(from_str(r#"{"bad" : "\u12G4"}"#).is_err());
+    }
+
+    #[test]
+    fn escaped_key_json() -> Result<()> {
+        let file_path: &str = "./json/escaped_key.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        assert!(json.map.len() == 4);
+        assert!(matches!(json.map.get("say \"hi\""), Some(Member::Num(_))));
+        assert!(matches!(json.map.get("back\\slash"), Some(Member::Bool(_))));
+        assert!(matches!(json.map.get("café"), Some(Member::Str(str)) if str == "accent"));
+        assert!(matches!(json.map.get("after"), Some(Member::Num(_))));
+
+        assert!(from_str(r#"{"bad\q" : 1}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn punctuation_string_json() -> Result<()> {
+        let file_path: &str = "./json/punctuation.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        assert!(json.map.len() == 6);
+        assert!(matches!(json.map.get("list"), Some(Member::Str(str)) if str == "a, b: c"));
+        assert!(matches!(json.map.get("weight"), Some(Member::Str(str)) if str == "1.5 kg"));
+        assert!(matches!(json.map.get("braces"), Some(Member::Str(str)) if str == "{[()]}"));
+        assert!(matches!(json.map.get("spaces"), Some(Member::Str(str)) if str == "  padded  "));
+        assert!(matches!(json.map.get("key, with: {stuff}"), Some(Member::Str(str)) if str == "ok"));
+
+        match json.map.get("arr") {
+            Some(Member::Arr(arr)) => {
+                let strings : Vec<&str> = arr.iter().map(|member| match member {
+                    Member::Str(str) => str.as_str(),
+                    _ => "",
+                }).collect();
+
+                assert!(strings == vec!["x, y", "}", " z ", "]"]);
+            }
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        assert!(from_str(r#""unterminated"#).is_err());
+
+        Ok(())
+    }
+
+    fn number_field(json : &JSON, key : &str) -> Option<Num> {
+        match json.map.get(key) {
+            Some(Member::Num(num)) => Some(num.value),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn big_number_json() -> Result<()> {
+        let file_path: &str = "./json/big_number.json";
+        let mut reader: BufReader<File> = passed_file(file_path)?;
+
+        let json: JSON = parse_json(&mut reader)?;
+
+        assert!(matches!(number_field(&json, "id"), Some(Num::Uint(u64::MAX))));
+        assert!(matches!(number_field(&json, "timestamp"), Some(Num::Uint(1700000000123))));
+        assert!(matches!(number_field(&json, "lowest"), Some(Num::Int(i64::MIN))));
+        assert!(matches!(number_field(&json, "exp"), Some(Num::Flt(flt)) if flt == 1e10));
+        assert!(matches!(number_field(&json, "small"), Some(Num::Flt(flt)) if flt == 0.0025));
+        assert!(matches!(number_field(&json, "neg_exp"), Some(Num::Flt(flt)) if flt == -150.0));
+        assert!(matches!(number_field(&json, "tenth"), Some(Num::Flt(flt)) if flt == 0.1));
+
+        match json.map.get("list") {
+            Some(Member::Arr(arr)) => {
+                assert!(matches!(&arr[0], Member::Num(num) if matches!(num.value, Num::Uint(12345))));
+                assert!(matches!(&arr[1], Member::Num(num) if matches!(num.value, Num::Flt(flt) if flt == 6.02214076e23)));
+            }
+            _ => return Err(anyhow!("No array found")),
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn number_overflow_json() {
+        assert!(from_str("18446744073709551616").is_err());
+        assert!(from_str("-9223372036854775809").is_err());
+        assert!(from_str("1e400").is_err());
+        assert!(from_str(r#"{"a" : 1x}"#).is_err());
+        assert!(from_str(r#"{"a" : 1e}"#).is_err());
+        assert!(from_str("[1 2]").is_err());
+    }
+
+    #[test]
+    fn arbitrary_precision_json() -> Result<()> {
+        let options = ParserOptions { arbitrary_precision : true, ..Default::default() };
+
+        let file = fs::File::open("./json/precise_number.json")?;
+
+        let json = match from_reader_with(file, options)? {
+            Member::JSON(json) => json,
+            _ => return Err(anyhow!("No JSON found")),
+        };
+
+        let number = |key : &str| match json.map.get(key) {
+            Some(Member::Num(num)) => num.clone(),
+            _ => panic!("No number for {}", key),
+        };
+
+        assert!(number("price").as_str() == Some("0.1"));
+        assert!(number("price").to_f64() == Some(0.1));
+
+        let huge = number("huge");
+        assert!(matches!(huge.value, Num::Big));
+        assert!(huge.to_u128() == Some(123456789012345678901234567890));
+        assert!(Member::Num(huge).to_string() == "123456789012345678901234567890");
+
+        assert!(matches!(number("negative").value, Num::Big));
+        assert!(number("negative").to_i128() == Some(-98765432109876543210));
+
+        assert!(matches!(number("exp").value, Num::Flt(flt) if flt == 1500.0));
+        assert!(Member::Num(number("exp")).to_string() == "1.50E+3");
+        assert!(Member::Num(number("beyond")).to_string() == "1e400");
+        assert!(number("beyond").to_f64().is_none());
+        assert!(json["beyond"].as_f64().is_none());
+        assert!(number("huge").to_f64() == Some(1.2345678901234568e29));
+
+        let file = fs::File::open("./json/precise_number.json")?;
+
+        assert!(from_reader(file).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn conformance_json() -> Result<()> {
+        let strict = ParserOptions { strict : true, ..Default::default() };
+
+        for entry in fs::read_dir("./json/conformance")? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+            let result = from_reader_with(fs::File::open(&path)?, strict);
+
+            if name.starts_with("y_") {
+                assert!(result.is_ok(), "{} should be accepted: {:?}", name, result.err());
+            }
+            else if name.starts_with("n_") {
+                assert!(result.is_err(), "{} should be rejected", name);
+            }
+            else if name.starts_with("i_") {
+                check_implementation_defined(&name, result, &path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // RFC 8259 leaves these up to the parser, so this pins down what ours does.
+    fn check_implementation_defined(name : &str, result : Result<Member, ParseError>, path : &std::path::Path) -> Result<()> {
+        let precise = ParserOptions { arbitrary_precision : true, strict : true, ..Default::default() };
+        let first = |member : Member| member.as_array().and_then(|array| array.first().cloned());
+
+        match name {
+            "i_number_huge_exponent.json" | "i_number_very_big_int.json" => {
+                assert!(matches!(result, Err(ParseError::NumberOverflow { .. })), "{}: {:?}", name, result);
+
+                let number = first(from_reader_with(fs::File::open(path)?, precise)?);
+                assert!(matches!(number, Some(Member::Num(num)) if num.value == Num::Big), "{}", name);
+            }
+            "i_string_invalid_utf8.json" => {
+                assert!(matches!(result, Err(ParseError::InvalidUtf8 { byte : 0xff, .. })), "{}: {:?}", name, result);
+            }
+            "i_string_lone_surrogate.json" => {
+                assert!(matches!(result, Err(ParseError::InvalidEscape { .. })), "{}: {:?}", name, result);
+            }
+            "i_structure_deep_nesting.json" => {
+                assert!(matches!(result, Err(ParseError::TooDeep { .. })), "{}: {:?}", name, result);
+
+                let deeper = ParserOptions { max_depth : 1000, ..Default::default() };
+                assert!(from_reader_with(fs::File::open(path)?, deeper).is_ok(), "{}", name);
+            }
+            _ => panic!("{} has no expected behaviour", name),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_json() -> Result<()> {
+        let strict = ParserOptions { strict : true, ..Default::default() };
+
+        for input in ["[1,]", r#"{"a" : 1,}"#, "[007]", "[\u{c}1]"] {
+            assert!(from_str(input).is_ok(), "{} should be accepted by default", input);
+            assert!(from_str_with(input, strict).is_err(), "{} should be rejected when strict", input);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn error_position_json() {
+        let position = |line, column, offset| Position { line, column, offset };
+
+        let parse_error = |input : &[u8]| match from_slice(input) {
+            Err(err) => err,
+            Ok(_) => panic!("{} should not parse", String::from_utf8_lossy(input)),
+        };
+
+        let err = parse_error(b"{\n  \"a\" : 1,\n  \"b\" 2\n}");
+        assert!(matches!(err, ParseError::UnexpectedCharacter { found : '2', .. }));
+        assert!(err.position() == position(3, 7, 19));
+        assert!(err.to_string() == "Unexpected '2', expected ':' after object key at line 3, column 7");
+
+        let err = parse_error(b"[1, 99999999999999999999]");
+        assert!(matches!(err, ParseError::NumberOverflow { .. }));
+        assert!(err.position() == position(1, 5, 4));
+
+        let err = parse_error(b"[tru]");
+        assert!(matches!(err, ParseError::InvalidLiteral { .. }));
+        assert!(err.position() == position(1, 2, 1));
+
+        let err = parse_error(b"[1, 2");
+        assert!(matches!(err, ParseError::UnexpectedEof { .. }));
+        assert!(err.position() == position(1, 6, 5));
+
+        let err = parse_error(b"[\"caf\xc3\x28\"]");
+        assert!(matches!(err, ParseError::InvalidUtf8 { byte : 0x28, .. }));
+        assert!(err.position() == position(1, 6, 6));
+
+        let err = parse_error("[\"\u{e9}\", x]".as_bytes());
+        assert!(err.position() == position(1, 7, 7));
+
+        let err = parse_error(br#"["\q"]"#);
+        assert!(matches!(err, ParseError::InvalidEscape { ref sequence, .. } if sequence == "\\q"));
+    }
 
-                   return Ok((Member::Num(mem::take(num)),is_escaped));
-               }
-               Member::Bool(booler) =>{
+    #[test]
+    fn error_snippet_json() {
+        let source = "{\n  \"a\" : 1,\n  \"b\" 2\n}";
 
-                    if current_char.is_ascii_whitespace() {
-                        return Ok((Member::Bool(mem::take(booler)),is_escaped));
-                    }
+        let err = match from_str(source) {
+            Err(err) => err,
+            Ok(_) => panic!("Missing ':' should not parse"),
+        };
 
-                    let correct_char = booler.correct.as_bytes()[booler.index] as char;
+        let expected = [
+            "error: Unexpected '2'",
+            " --> line 3, column 7",
+            "  |",
+            "3 |   \"b\" 2",
+            "  |       ^",
+            "  = hint: expected ':' after object key",
+        ].join("\n");
 
-                    if current_char == correct_char && !current_char.is_ascii_whitespace() {
-                        booler.current.push(current_char);
-                        if booler.index < booler.correct.len()-1 {
-                            booler.index += 1;
-                        }
-                    }
-                    else {
-                        return Err(anyhow!("Incorrectly spelled boolean value"));
-                    }
+        assert_eq!(err.render(source), expected);
 
-                    return Ok((Member::Bool(mem::take(booler)),is_escaped));
-               }
+        let source = "{\n\t\"a\" : 1 \"b\" : 2\n}";
 
-                _ => {
-                   return Ok((Member::Empty,is_escaped));
-                }
-    };
-}
+        let err = match from_str(source) {
+            Err(err) => err,
+            Ok(_) => panic!("Missing ',' should not parse"),
+        };
 
-fn check_key(json_pair : &mut JSONPair) -> Result<()>{
-    
-    if json_pair.current_key {
-        json_pair.current_key = false;
+        assert!(err.render(source).ends_with("2 | \t\"a\" : 1 \"b\" : 2\n  | \t        ^\n  = hint: expected ',' or '}' after object member"));
     }
-    else {
-        json_pair.current_key = true;
-    }
-
-    Ok(())
-}
 
-fn checkset_string (data : &mut Member, is_escaped: bool, current_char : char) -> Result<(Member,bool)>{
-    let new_data : Member;
-    let mut new_escaped : bool = is_escaped; 
+    // A small xorshift generator so the fuzz tests below are repeatable
+    // without pulling in a crate for randomness.
+    struct Fuzzer(u64);
 
-    new_data = match data {
-        Member::Str(str) => {
-            let (new_string, escaped) = append_character(current_char, &str,is_escaped)?;
-            new_escaped = escaped;
+    impl Fuzzer {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
 
-            Member::Str(new_string)
+        fn below(&mut self, bound : usize) -> usize {
+            (self.next() % bound as u64) as usize
         }
-        _ => {
-            Member::Str(String::new())
+    }
+
+    // Parsing has to end in Ok or Err, never a panic, and so does rendering
+    // whatever error comes back.
+    fn survive(input : &[u8]) {
+        let strict = ParserOptions { strict : true, ..Default::default() };
+        let precise = ParserOptions { arbitrary_precision : true, ..Default::default() };
+        let collect = ParserOptions { duplicate_keys : DuplicateKeys::Collect, ..Default::default() };
+        let unique = ParserOptions { duplicate_keys : DuplicateKeys::Error, ..Default::default() };
+
+        for options in [ParserOptions::default(), strict, precise, collect, unique] {
+            if let Err(err) = from_slice_with(input, options) {
+                err.render(&String::from_utf8_lossy(input));
+            }
         }
-    };
+    }
 
-    Ok((new_data,new_escaped))
-}
+    #[test]
+    fn fuzz_random_bytes() {
+        let mut fuzzer = Fuzzer(0x9E3779B97F4A7C15);
 
-fn make_flt(data : &mut Member) -> Result<Member> {
+        for _ in 0..2000 {
+            let len = fuzzer.below(64);
+            let input : Vec<u8> = (0..len).map(|_| fuzzer.next() as u8).collect();
 
-    match *data{
-        Member::Num(mut nummer) => {
+            survive(&input);
+        }
+    }
 
-            nummer.value = match nummer.value {
-                Num::Uint(uint) => {
+    #[test]
+    fn fuzz_json_tokens() {
+        let tokens = [
+            "{", "}", "[", "]", ",", ":", "\"", "\\", "\\u", "d83d", "dc00", "tru", "e", "null", "false",
+            "-", "0", "1.5", "e+", "99999999999999999999", "1e999", " ", "\n", "\t", "\u{c}", "\u{e9}", "\u{1}", "x",
+        ];
 
-                    Num::Flt(uint as f32)
-                }
-                Num::Int(int) => {
-                    Num::Flt(int as f32)
-                }
-                _=> {
-                   Num::Flt(0.00)
-                }
-            };
+        let mut fuzzer = Fuzzer(0xDEADBEEFCAFEF00D);
 
-            nummer.digit = 0;
+        for _ in 0..5000 {
+            let len = fuzzer.below(24);
+            let input : String = (0..len).map(|_| tokens[fuzzer.below(tokens.len())]).collect();
 
-            return Ok(Member::Num(nummer));
-        }
-        _=> {
-            return Ok(mem::take(data));
+            survive(input.as_bytes());
         }
-    };
-}
+    }
 
-fn append_character(current_char : char, stringer : &String, mut is_escaped : bool) -> Result<(String,bool)>{
+    #[test]
+    fn fuzz_mutated_files() -> Result<()> {
+        let mut fuzzer = Fuzzer(0x0123456789ABCDEF);
 
-    let mut new_string = stringer.to_string();
+        let alphabet = b"{}[],:\"\\-.e0 ";
 
-    if current_char.is_alphanumeric() && !is_escaped {
-        new_string.push(current_char);
-    }
-    else if current_char == '\\' && !is_escaped {
-        is_escaped = true;
-    }
-    else {
-        match current_char {
-            '\\' => {
-                new_string.push('\\');
-            }
-            '"' => {
-                new_string.push('"');
-            }
-            'n' => {
-                new_string.push('\n');
-            }
-            't' => {
-                new_string.push('\t');
-            }
-            'r' => {
-                new_string.push('\r');
-            }
-            _=> {
-                new_string.push(current_char);
+        let path = env::temp_dir().join(format!("painful_json_fuzz_{}.json", std::process::id()));
+
+        for entry in fs::read_dir("./json")? {
+            let fixture = entry?.path();
+
+            if fixture.is_dir() {
+                continue;
             }
-        }
 
-        is_escaped = false;
-    }
-    
-    return Ok((new_string,is_escaped))
+            let original = fs::read(&fixture)?;
 
+            for _ in 0..20 {
+                let mut input = original.clone();
 
-}
+                for _ in 0..=fuzzer.below(4) {
+                    let index = fuzzer.below(input.len() + 1);
 
+                    match fuzzer.below(4) {
+                        0 if index < input.len() => input[index] = fuzzer.next() as u8,
+                        1 if index < input.len() => { input.remove(index); }
+                        2 => input.truncate(index),
+                        _ => input.insert(index, alphabet[fuzzer.below(alphabet.len())]),
+                    }
+                }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+                survive(&input);
 
-    fn passed_file(file_path : &str) -> Result<BufReader<File>>{
-    
-        let reader = read_file(file_path)?;
-        
-        Ok(reader)
+                fs::write(&path, &input)?;
+                let _ = parse_json(&mut read_file(path.to_str().unwrap_or_default())?);
+            }
+        }
+
+        fs::remove_file(&path)?;
+
+        Ok(())
     }
 
     #[test]
-    fn correct_file_path() -> () {
-        let args = vec!["".to_string(), "file.json".to_string()];
-        
-        assert_eq!(parse_args(&args), "file.json");
-    }
+    fn nesting_depth_json() -> Result<()> {
+        let position = |line, column, offset| Position { line, column, offset };
 
+        let deep = "[".repeat(100_000) + &"]".repeat(100_000);
 
-    #[test]
-    #[should_panic]
-    fn no_file_path() -> () {
-        let args = vec!["".to_string()];
+        match from_str(&deep) {
+            Err(err @ ParseError::TooDeep { max_depth : 128, .. }) => assert!(err.position() == position(1, 129, 128)),
+            _ => panic!("100000 nested arrays should be too deep"),
+        }
 
-        parse_args(&args);
-    }
+        let deep = r#"{"a":"#.repeat(100_000) + "1" + &"}".repeat(100_000);
 
-    #[test]
-    #[should_panic]
-    fn no_file_type() -> () {
-        let args = vec!["this.".to_string()];
+        match from_str(&deep) {
+            Err(err @ ParseError::TooDeep { .. }) => assert!(err.position() == position(1, 641, 640)),
+            _ => panic!("100000 nested objects should be too deep"),
+        }
+
+        let unclosed = fs::File::open("./json/conformance/n_structure_100000_opening_arrays.json")?;
+        assert!(matches!(from_reader(unclosed), Err(ParseError::TooDeep { .. })));
+
+        let limit = "[".repeat(128) + &"]".repeat(128);
+        assert!(from_str(&limit).is_ok());
 
-        parse_args(&args);
+        let shallow = ParserOptions { max_depth : 2, ..ParserOptions::default() };
+        assert!(from_str_with("[{}]", shallow).is_ok());
+        assert!(matches!(from_str_with("[{\"a\":[]}]", shallow), Err(ParseError::TooDeep { .. })));
+        assert!(from_str_with("1", ParserOptions { max_depth : 0, ..ParserOptions::default() }).is_ok());
+
+        Ok(())
     }
 
     #[test]
-    fn no_json() -> Result<()> {
-        let fake_file = "       ";
+    fn serialize_json() -> Result<()> {
+        let input = r#"[ "a\"b\\c/\n\u0001é", 1, -2, 1.5, 2.0, 1e300, true, false, null, [], {}, [[{"k" : [1]}]] ]"#;
 
-        let mut json = JSON {
-            map : HashMap::new(),
-            current_object : false,
-            is_json : false,
-        };
+        let compact = from_str(input)?.to_string();
 
-        let mut json_pair = JSONPair {
-            key : String::new(),
-            value : Member::Empty,
-            current_key : false,
-            current_value : false,
-            is_escaped : false
-        };
+        assert_eq!(compact, r#"["a\"b\\c/\n\u0001é",1,-2,1.5,2.0,1e300,true,false,null,[],{},[[{"k":[1]}]]]"#);
+        assert_eq!(from_str(&compact)?.to_string(), compact);
 
-        let mut array = Array {
-            value : Vec::new(),
-            data : Member::Empty,
-            is_array : false,
-            is_escaped : false
+        let json = match from_str(r#"{ "key \"quoted\"" : { "inner" : "value" } }"#)? {
+            Member::JSON(json) => json,
+            _ => return Err(anyhow!("No JSON found")),
         };
 
-        for char in fake_file.chars() {
-            parse_character(char, &mut json_pair, &mut json,&mut array)?;
-        }
-
-        assert!(!json.is_json);
+        assert_eq!(json.to_string(), r#"{"key \"quoted\"":{"inner":"value"}}"#);
 
         Ok(())
     }
 
     #[test]
-    fn json() -> Result<()> {
-        
-        let file_path = "./json/empty.json"; 
-        let mut reader = passed_file(file_path)?;
+    fn round_trip_json() -> Result<()> {
+        for fixture in ["./json/nested.json", "./json/nested_array.json", "./json/top_level_array.json", "./json/utf8.json", "./json/punctuation.json"] {
+            let compact = from_reader(fs::File::open(fixture)?)?.to_string();
 
-        let json = parse_json(&mut reader)?;
+            assert_eq!(from_str(&compact)?.to_string(), compact);
+        }
 
-        assert!(json.is_json);
         Ok(())
     }
 
     #[test]
-    fn bool_json() -> Result<()> {
-        let file_path = "./json/bool.json";
- 
-        let mut reader = passed_file(file_path)?;
+    fn pretty_json() -> Result<()> {
+        let document = from_str(r#"{"servers" : [{"ports" : [80, 443], "tags" : []}, {}]}"#)?;
 
-        let json = parse_json(&mut reader)?;
+        let expected = [
+            "{",
+            "    \"servers\": [",
+            "        {",
+            "            \"ports\": [",
+            "                80,",
+            "                443",
+            "            ],",
+            "            \"tags\": []",
+            "        },",
+            "        {}",
+            "    ]",
+            "}",
+        ].join("\n");
 
-        let field = json.map.get("bool").unwrap_or(&Member::Empty);
+        assert_eq!(document.to_string_pretty(), expected);
 
-       match field {
-            Member::Bool(bool)=>{
-                assert!(bool.value);
-            }
-            _=> { panic!("Not a boolean");}
-        };
+        let options = PrettyOptions { indent : Indent::Tabs(1), space_after_colon : false, trailing_newline : true };
+
+        assert_eq!(from_str(r#"{"a" : [1, "x"]}"#)?.to_string_pretty_with(options), "{\n\t\"a\":[\n\t\t1,\n\t\t\"x\"\n\t]\n}\n");
+
+        let options = PrettyOptions { indent : Indent::Spaces(2), ..Default::default() };
+
+        assert_eq!(from_str("[[true], null]")?.to_string_pretty_with(options), "[\n  [\n    true\n  ],\n  null\n]");
+        assert_eq!(from_str("\"scalar\"")?.to_string_pretty(), "\"scalar\"");
 
         Ok(())
     }
 
     #[test]
-    fn uint_json() -> Result<()> {
-        let file_path: &str = "./json/number.json";
-
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+    fn canonical_number_json() {
+        // The sample values from RFC 8785 appendix B.
+        let samples : [(u64, Option<&str>); 26] = [
+            (0x0000000000000000, Some("0")),
+            (0x8000000000000000, Some("0")),
+            (0x0000000000000001, Some("5e-324")),
+            (0x8000000000000001, Some("-5e-324")),
+            (0x7fefffffffffffff, Some("1.7976931348623157e+308")),
+            (0xffefffffffffffff, Some("-1.7976931348623157e+308")),
+            (0x4340000000000000, Some("9007199254740992")),
+            (0xc340000000000000, Some("-9007199254740992")),
+            (0x4430000000000000, Some("295147905179352830000")),
+            (0x7fffffffffffffff, None),
+            (0x7ff0000000000000, None),
+            (0x44b52d02c7e14af5, Some("9.999999999999997e+22")),
+            (0x44b52d02c7e14af6, Some("1e+23")),
+            (0x44b52d02c7e14af7, Some("1.0000000000000001e+23")),
+            (0x444b1ae4d6e2ef4e, Some("999999999999999700000")),
+            (0x444b1ae4d6e2ef4f, Some("999999999999999900000")),
+            (0x444b1ae4d6e2ef50, Some("1e+21")),
+            (0x3eb0c6f7a0b5ed8c, Some("9.999999999999997e-7")),
+            (0x3eb0c6f7a0b5ed8d, Some("0.000001")),
+            (0x41b3de4355555553, Some("333333333.3333332")),
+            (0x41b3de4355555554, Some("333333333.33333325")),
+            (0x41b3de4355555555, Some("333333333.3333333")),
+            (0x41b3de4355555556, Some("333333333.3333334")),
+            (0x41b3de4355555557, Some("333333333.33333343")),
+            (0xbecbf647612f3696, Some("-0.0000033333333333333333")),
+            (0x43143ff3c1cb0959, Some("1424953923781206.2")),
+        ];
+
+        for (bits, expected) in samples {
+            assert_eq!(canonical_number(f64::from_bits(bits)).ok().as_deref(), expected, "{:#018x}", bits);
+        }
+    }
 
-        let json: JSON = parse_json(&mut reader)?;
+    #[test]
+    fn canonical_json() -> Result<()> {
+        // The example from RFC 8785 section 3.2.2.
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+
+        let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+
+        assert_eq!(from_str(input)?.to_canonical_string()?, expected);
+
+        // The property sorting example from section 3.2.3.
+        let input = r#"{
+            "€": "Euro Sign",
+            "\r": "Carriage Return",
+            "דּ": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "😀": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "ö": "Latin Small Letter O With Diaeresis"
+        }"#;
+
+        let json = match from_str(input)? {
+            Member::JSON(json) => json,
+            _ => return Err(anyhow!("No JSON found")),
+        };
 
-        let field: &Member = json.map.get("uinter").unwrap_or( &Member::Empty);
+        let canonical = json.to_canonical_string()?;
 
-        let value :Num = match *field {
-            Member::Num(num)=>{
-                num.value
-            }
-            _ => {Num::Empty}
-        };
+        let order = ["Carriage Return", "One", "Control", "Latin Small Letter O With Diaeresis", "Euro Sign", "Emoji: Grinning Face", "Hebrew Letter Dalet With Dagesh"];
+        let positions : Vec<usize> = order.iter().filter_map(|value| canonical.find(value)).collect();
 
-        match value {
-            Num::Uint(uint) => {
-                assert!(uint == 22);
-            }
-            _ => {
-                assert!(false)
-            }
-        };
+        assert!(positions.len() == order.len() && positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", canonical);
 
         Ok(())
     }
 
     #[test]
-    fn int_json() -> Result<()> {
-        let file_path: &str = "./json/number.json";
-
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+    fn key_order_json() -> Result<()> {
+        let mut json = match from_reader(fs::File::open("./json/punctuation.json")?)? {
+            Member::JSON(json) => json,
+            _ => return Err(anyhow!("No JSON found")),
+        };
 
-        let json: JSON = parse_json(&mut reader)?;
+        let keys : Vec<&String> = json.map.keys().collect();
+        assert_eq!(keys, ["list", "weight", "braces", "spaces", "key, with: {stuff}", "arr"]);
 
-        let field: &Member = json.map.get("inter").unwrap_or( &Member::Empty);
+        json.map.remove("weight");
+        json.map.insert(String::from("list"), Member::Null);
+        json.map.insert(String::from("added"), Member::Null);
 
-        let value :Num = match *field {
-            Member::Num(num)=>{
-                num.value
-            }
-            _ => {Num::Empty}
-        };
+        let keys : Vec<&String> = json.map.keys().collect();
+        assert_eq!(keys, ["list", "braces", "spaces", "key, with: {stuff}", "arr", "added"]);
 
-        match value {
-            Num::Int(int) => {
-                assert!(int == -22);
-            }
-            _ => {
-                assert!(false)
-            }
-        };
+        assert!(matches!(json.map.get("arr"), Some(Member::Arr(_))));
+        assert!(json.map.get("weight").is_none());
+        assert!(json.to_string().starts_with(r#"{"list":null,"braces":"{[()]}""#));
 
         Ok(())
     }
 
     #[test]
-    fn flt_json() -> Result<()> {
-        let file_path: &str = "./json/number.json";
-
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+    fn duplicate_key_json() -> Result<()> {
+        let input = r#"{"a" : 1, "b" : [0], "a" : 2, "b" : [1], "a" : 3}"#;
 
-        let json: JSON = parse_json(&mut reader)?;
+        let with = |duplicate_keys| ParserOptions { duplicate_keys, ..Default::default() };
 
-        let field: &Member = json.map.get("floater").unwrap_or( &Member::Empty);
+        assert_eq!(from_str(input)?.to_string(), r#"{"a":3,"b":[1]}"#);
+        assert_eq!(from_str_with(input, with(DuplicateKeys::KeepFirst))?.to_string(), r#"{"a":1,"b":[0]}"#);
+        assert_eq!(from_str_with(input, with(DuplicateKeys::Collect))?.to_string(), r#"{"a":[1,2,3],"b":[[0],[1]]}"#);
+        assert_eq!(from_str_with(r#"{"a" : [1, 2], "b" : 3}"#, with(DuplicateKeys::Collect))?.to_string(), r#"{"a":[[1,2]],"b":[3]}"#);
 
-        let value :Num = match *field {
-            Member::Num(num)=>{
-                num.value
+        match from_str_with(input, with(DuplicateKeys::Error)) {
+            Err(ParseError::DuplicateKey { key, position }) => {
+                assert_eq!(key, "a");
+                assert!(position == Position { line : 1, column : 22, offset : 21 });
             }
-            _ => {Num::Empty}
-        };
+            _ => return Err(anyhow!("Duplicate key was not reported")),
+        }
 
-        match value {
-            Num::Flt(flt) => {
-                assert!(flt == -22.1);
-            }
-            _ => {
-                assert!(false)
-            }
-        };
+        // Keys only clash within the same object.
+        assert!(from_str_with(r#"{"a" : {"a" : 1}, "b" : [{"a" : 2}, {"a" : 3}]}"#, with(DuplicateKeys::Error)).is_ok());
 
         Ok(())
     }
 
     #[test]
-    fn string_json() -> Result<()> {
-        let file_path: &str = "./json/string.json";
-
-        let mut reader: BufReader<File> = passed_file(file_path)?;
-
-        let json: JSON = parse_json(&mut reader)?;
+    fn accessor_json() -> Result<()> {
+        let doc = from_reader(fs::File::open("./json/servers.json")?)?;
+
+        assert_eq!(doc["servers"][0]["port"].as_u64(), Some(8080));
+        assert_eq!(doc["servers"][0]["host"].as_str(), Some("alpha.internal"));
+        assert_eq!(doc["servers"][1]["enabled"].as_bool(), Some(false));
+        assert_eq!(doc["servers"][1]["weight"].as_f64(), Some(0.25));
+        assert_eq!(doc["offset"].as_i64(), Some(-30));
+        assert_eq!(doc["offset"].as_u64(), None);
+        assert_eq!(doc["servers"][1]["weight"].as_i64(), None);
+        assert!(doc["fallback"].is_null());
+        assert_eq!(doc["servers"].as_array().map(|servers| servers.len()), Some(2));
+        assert_eq!(doc["servers"][0].as_object().map(|server| server.map.len()), Some(3));
+
+        // Misses at any depth land on the shared null instead of panicking.
+        assert!(doc["missing"]["deeper"][3].is_null());
+        assert!(doc["servers"][7]["port"].is_null());
+        assert!(doc["offset"]["port"].is_null());
+        assert_eq!(doc["servers"][0]["port"].as_str(), None);
+
+        let json = match doc {
+            Member::JSON(json) => json,
+            _ => return Err(anyhow!("No JSON found")),
+        };
 
-        let field: &Member = json.map.get("stringer").unwrap_or( &Member::Empty);
+        assert_eq!(json["servers"][1]["port"].as_i64(), Some(9090));
 
-        let value : String = match field {
-            Member::Str(str)=>{
-                str.to_string()
-            }
-            _ => {String::from("")}
+        let number = |member : &Member| match member {
+            Member::Num(num) => num.value(),
+            _ => panic!("{} is not a number", member),
         };
 
-        assert!(value == "hello");
+        assert_eq!(number(&from_str("1")?), Num::Uint(1));
+        assert_eq!(number(&from_str("1.0")?), Num::Flt(1.0));
+        assert_eq!(number(&json["offset"]), Num::Int(-30));
+        assert_eq!(Number::new(Num::Flt(2.5)).map(Member::Num).and_then(|member| member.as_f64()), Some(2.5));
+        assert_eq!(Number::new(Num::Int(-4)).and_then(|number| number.as_i64()), Some(-4));
+        assert!(Number::new(Num::Big).is_none());
+
+        let big = Number::from_digits("123456789012345678901234567890")?;
+        assert_eq!(big.value(), Num::Big);
+        assert_eq!(big.to_u128(), Some(123456789012345678901234567890));
+        assert_eq!(Member::Num(big).to_string(), "123456789012345678901234567890");
+        assert_eq!(Number::from_digits("0.10")?.as_str(), Some("0.10"));
+        assert!(matches!(Number::from_digits("01"), Err(ParseError::InvalidNumber { .. })));
+        assert!(Number::from_digits("1 ").is_err());
 
         Ok(())
     }
 
     #[test]
-    fn escaped_quote_json() -> Result<()> {
-        let file_path: &str = "./json/escaped_string.json";
+    fn edit_json() -> Result<()> {
+        let mut doc = from_reader(fs::File::open("./json/servers.json")?)?;
 
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+        doc["servers"][0]["port"] = from_str("9000")?;
+        doc["servers"][1].insert("region", from_str(r#""eu""#)?)?;
+        assert!(doc["servers"][1].remove("weight").is_some());
+        doc["servers"].push(from_str(r#"{"host" : "gamma.internal"}"#)?)?;
 
-        let json: JSON = parse_json(&mut reader)?;
+        // Null and missing values turn into whatever they are edited as.
+        doc["fallback"]["host"] = from_str(r#""delta""#)?;
+        doc["tags"][0] = from_str(r#""a""#)?;
+        doc["tags"][1] = from_str(r#""c""#)?;
 
-        let field: &Member = json.map.get("stringer").unwrap_or( &Member::Empty);
+        if let Some(offset) = doc.get_mut("offset") {
+            *offset = from_str("0")?;
+        }
 
-        let value : String = match field {
-            Member::Str(str)=>{
-                str.to_string()
-            }
-            _ => {String::from("")}
+        doc.entry("offset")?.and_modify(|offset| *offset = Member::Null).or_insert(from_str("1")?);
+        doc.entry("retries")?.and_modify(|retries| *retries = Member::Null).or_insert(from_str("3")?);
+
+        // Values of any other type are kept rather than replaced.
+        assert!(doc["servers"][0]["port"].push(Member::Null).is_err());
+        assert!(doc["servers"][0]["host"].insert("k", Member::Null).is_err());
+        assert!(doc["servers"][0]["enabled"].entry("k").is_err());
+        assert_eq!(doc["servers"][0]["host"].as_str(), Some("alpha.internal"));
+
+        assert_eq!(doc.to_string(), concat!(
+            r#"{"servers":[{"host":"alpha.internal","port":9000,"enabled":true},"#,
+            r#"{"host":"beta.internal","port":9090,"enabled":false,"region":"eu"},{"host":"gamma.internal"}],"#,
+            r#""offset":null,"fallback":{"host":"delta"},"tags":["a","c"],"retries":3}"#,
+        ));
+
+        assert!(doc["retries"].get_mut("anything").is_none());
+        assert!(doc["retries"].remove("anything").is_none());
+        assert!(doc.get_mut("missing").is_none());
+
+        let mut json = match doc {
+            Member::JSON(json) => json,
+            _ => return Err(anyhow!("No JSON found")),
         };
 
-        assert!(value == "hello\"");
+        json["retries"] = from_str("5")?;
+        json["added"] = from_str("true")?;
+        assert!(json.remove("tags").is_some());
+        assert_eq!(json.get("retries").and_then(|retries| retries.as_u64()), Some(5));
+        assert!(json.to_string().ends_with(r#""retries":5,"added":true}"#));
 
         Ok(())
     }
 
     #[test]
-    fn escaped_slash_json() -> Result<()> {
-        let file_path: &str = "./json/escaped_string.json";
+    #[should_panic(expected = "Can't index into a number with 'port'")]
+    fn index_mut_wrong_type() {
+        let mut doc = json!({ "servers" : 5 });
 
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+        doc["servers"]["port"] = json!(80);
+    }
 
-        let json: JSON = parse_json(&mut reader)?;
+    #[test]
+    #[should_panic(expected = "Index 18446744073709551615 is past the end of an array of length 1")]
+    fn index_mut_past_end() {
+        let mut doc = json!({ "tags" : ["a"] });
 
-        let field: &Member = json.map.get("slasher").unwrap_or( &Member::Empty);
+        doc["tags"][usize::MAX] = json!("b");
+    }
 
-        let value : String = match field {
-            Member::Str(str)=>{
-                str.to_string()
-            }
-            _ => {String::from("")}
-        };
+    #[test]
+    fn build_json() -> Result<()> {
+        let port = 8080;
+        let tags = vec!["web", "edge"];
+        let backup : Option<&str> = None;
+
+        let doc = json!({
+            "name" : "alpha",
+            "ports" : [80, port + 1, -1, 2.5],
+            "enabled" : true,
+            "tags" : tags,
+            "backup" : backup,
+            "limits" : { "cpu" : 0.5, "memory" : null, "nested" : [[], {}, [false]] },
+            ("computed ".to_string() + "key") : port,
+        });
+
+        assert_eq!(doc.to_string(), concat!(
+            r#"{"name":"alpha","ports":[80,8081,-1,2.5],"enabled":true,"tags":["web","edge"],"backup":null,"#,
+            r#""limits":{"cpu":0.5,"memory":null,"nested":[[],{},[false]]},"computed key":8080}"#,
+        ));
+
+        assert_eq!(doc.to_string(), from_str(&doc.to_string())?.to_string());
+        assert_eq!(json!([1, "two", null]).to_string(), r#"[1,"two",null]"#);
+        assert_eq!(json!("scalar").to_string(), r#""scalar""#);
+        assert!(json!(null).is_null());
+
+        assert_eq!(Member::from(-5i8).as_i64(), Some(-5));
+        assert_eq!(Member::from(7usize).as_u64(), Some(7));
+        assert_eq!(Member::from(1.5f32).as_f64(), Some(1.5));
+        assert_eq!(Member::from(Some(true)).as_bool(), Some(true));
+        assert_eq!(Member::from(String::from("owned")).as_str(), Some("owned"));
+
+        let sorted : BTreeMap<&str, u32> = [("b", 2), ("a", 1)].into_iter().collect();
+        assert_eq!(Member::from(sorted).to_string(), r#"{"a":1,"b":2}"#);
+
+        let hashed : HashMap<String, Vec<bool>> = [(String::from("flags"), vec![true, false])].into_iter().collect();
+        assert_eq!(Member::from(hashed).to_string(), r#"{"flags":[true,false]}"#);
 
-        assert!(value == "hello\\");
+        Ok(())
+    }
+
+    #[test]
+    fn large_build_json() -> Result<()> {
+        let base = 1000;
+        let key = String::from("computed");
+
+        let config = json!({
+            "m01" : 1, "m02" : 2, "m03" : 3, "m04" : 4, "m05" : -5,
+            "m06" : 6, "m07" : base + 7, "m08" : 8, "m09" : 9, "m10" : -10,
+            "m11" : [11, null], "m12" : 12, "m13" : { "n" : 13 }, "m14" : base + 14, "m15" : -15,
+            "m16" : 16, "m17" : 17, "m18" : 18, "m19" : 19, "m20" : -20,
+            "m21" : base + 21, "m22" : [22, null], "m23" : 23, "m24" : 24, "m25" : -25,
+            "m26" : { "n" : 26 }, "m27" : 27, "m28" : base + 28, "m29" : 29, "m30" : -30,
+            "m31" : 31, "m32" : 32, "m33" : [33, null], "m34" : 34, "m35" : base + 35,
+            "m36" : 36, "m37" : 37, "m38" : 38, "m39" : { "n" : 39 }, "m40" : -40,
+            "m41" : 41, "m42" : base + 42, "m43" : 43, "m44" : [44, null], "m45" : -45,
+            "m46" : 46, "m47" : 47, "m48" : 48, "m49" : base + 49, "m50" : -50,
+            "m51" : 51, "m52" : { "n" : 52 }, "m53" : 53, "m54" : 54, "m55" : [55, null],
+            "m56" : base + 56, "m57" : 57, "m58" : 58, "m59" : 59, "m60" : -60,
+            "m61" : 61, "m62" : 62, "m63" : base + 63, "m64" : 64, "m65" : { "n" : 65 },
+            "m66" : [66, null], "m67" : 67, "m68" : 68, "m69" : 69, "m70" : base + 70,
+            "m71" : 71, "m72" : 72, "m73" : 73, "m74" : 74, "m75" : -75,
+            "m76" : 76, "m77" : [77, null], "m78" : { "n" : 78 }, "m79" : 79, "m80" : -80,
+            key.as_str() : "multi-token key",
+        });
+
+        let mut expected = String::from("{");
+
+        for i in 1..=80 {
+            let value = match i {
+                _ if i % 11 == 0 => format!("[{},null]", i),
+                _ if i % 13 == 0 => format!(r#"{{"n":{}}}"#, i),
+                _ if i % 7 == 0 => (base + i).to_string(),
+                _ if i % 5 == 0 => format!("-{}", i),
+                _ => i.to_string(),
+            };
+
+            expected.push_str(&format!(r#""m{:02}":{},"#, i, value));
+        }
+
+        expected.push_str(r#""computed":"multi-token key"}"#);
+        assert_eq!(config.to_string(), expected);
+
+        let list = json!([
+            null, 1, 2, -3, 4, 5, 6, 7, 8, 9, 10, 11, 12, -13, 14, 15, 16, 17, 18, 19,
+            20, 21, 22, -23, 24, 25, 26, 27, 28, 29, 30, 31, 32, -33, 34, 35, 36, 37, 38, 39,
+            40, 41, 42, -43, 44, 45, 46, 47, 48, 49, null, 51, 52, -53, 54, 55, 56, 57, 58, 59,
+            60, 61, 62, -63, 64, 65, 66, 67, 68, 69, 70, 71, 72, -73, 74, 75, 76, 77, 78, 79,
+            80, 81, 82, -83, 84, 85, 86, 87, 88, 89, 90, 91, 92, -93, 94, 95, 96, 97, 98, 99,
+            null, 101, 102, -103, 104, 105, 106, 107, 108, 109, 110, 111, 112, -113, 114, 115, 116, 117, 118, 119,
+            120, 121, 122, -123, 124, 125, 126, 127, 128, 129, 130, 131, 132, -133, 134, 135, 136, 137, 138, 139,
+            140, 141, 142, -143, 144, 145, 146, 147, 148, 149, null, 151, 152, -153, 154, 155, 156, 157, 158, 159,
+            160, 161, 162, -163, 164, 165, 166, 167, 168, 169, 170, 171, 172, -173, 174, 175, 176, 177, 178, 179,
+            180, 181, 182, -183, 184, 185, 186, 187, 188, 189, 190, 191, 192, -193, 194, 195, 196, 197, 198, 199,
+            null, 201, 202, -203, 204, 205, 206, 207, 208, 209, 210, 211, 212, -213, 214, 215, 216, 217, 218, 219,
+            220, 221, 222, -223, 224, 225, 226, 227, 228, 229, 230, 231, 232, -233, 234, 235, 236, 237, 238, 239,
+            240, 241, 242, -243, 244, 245, 246, 247, 248, 249, null, 251, 252, -253, 254, 255, 256, 257, 258, 259,
+            260, 261, 262, -263, 264, 265, 266, 267, 268, 269, 270, 271, 272, -273, 274, 275, 276, 277, 278, 279,
+            280, 281, 282, -283, 284, 285, 286, 287, 288, 289, 290, 291, 292, -293, 294, 295, 296, 297, 298, 299,
+        ]);
+
+        let expected : Vec<String> = (0..300).map(|j| match j {
+            _ if j % 50 == 0 => String::from("null"),
+            _ if j % 10 == 3 => format!("-{}", j),
+            _ => j.to_string(),
+        }).collect();
+
+        assert_eq!(list.to_string(), format!("[{}]", expected.join(",")));
 
         Ok(())
     }
 
     #[test]
-    fn empty_array_json() -> Result<()> {
-        let file_path: &str = "./json/empty_array.json";
+    fn value_types_json() -> Result<()> {
+        let file_path = "./json/servers.json";
+        let mut reader = passed_file(file_path)?;
 
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+        let parsed = Member::JSON(parse_json(&mut reader)?);
 
-        let json: JSON = parse_json(&mut reader)?;
+        let built = json!({
+            "servers" : [
+                { "host" : "alpha.internal", "port" : 8080, "enabled" : true },
+                { "host" : "beta.internal", "port" : 9090, "enabled" : false, "weight" : 0.25 }
+            ],
+            "offset" : -30,
+            "fallback" : null
+        });
 
-        let field: &Member = json.map.get("arr").unwrap_or( &Member::Empty);
+        assert_eq!(parsed, built);
 
-        match field {
-            Member::Arr(arr)=>{
-                assert!(arr.len() == 0);
-            }
-            _=>{
-               return Err(anyhow!("No array found")); 
-            }
-        };
+        let mut copy = parsed.clone();
+        copy["servers"][1]["port"] = Member::from(9091);
+
+        assert_ne!(copy, parsed);
+        assert_eq!(parsed["servers"][1]["port"].as_u64(), Some(9090));
+
+        assert_eq!(format!("{:?}", json!({ "a" : [true, null] })), r#"JSON(JSON { map: {"a": Arr([Bool(true), Null])} })"#);
+        assert_eq!(Member::default(), Member::Null);
 
         Ok(())
     }
 
     #[test]
-    fn array_json() -> Result<()> {
-        let file_path: &str = "./json/array.json";
-        let mut reader: BufReader<File> = passed_file(file_path)?;
+    fn equality_json() -> Result<()> {
+        let first = from_str(r#"{"name":"alpha","ports":[80,443],"limits":{"cpu":1,"memory":0.5}}"#)?;
+        let reordered = from_str(r#"{"limits":{"memory":5e-1,"cpu":1.0},"ports":[80,443.0],"name":"alpha"}"#)?;
+
+        assert_eq!(first, reordered);
+        assert_ne!(first, from_str(r#"{"name":"alpha","ports":[443,80],"limits":{"cpu":1,"memory":0.5}}"#)?);
+        assert_ne!(first, from_str(r#"{"name":"alpha","ports":[80,443],"limits":{"cpu":1}}"#)?);
+
+        assert_eq!(json!(1), json!(1.0));
+        assert_eq!(json!(-0.0), json!(0));
+        assert_eq!(json!(-7), from_str("-7e0")?);
+        assert_ne!(json!(1), json!(1.5));
+        assert_ne!(json!(1), json!(true));
+        assert_ne!(json!(null), json!(0));
+
+        // Integers meet floats without rounding.
+        assert_ne!(json!(u64::MAX), json!(u64::MAX as f64));
+        assert_ne!(json!(9007199254740993u64), json!(9007199254740992.0));
+        assert_eq!(json!(f64::NAN), json!(f64::NAN));
+
+        let precise = ParserOptions { arbitrary_precision : true, ..ParserOptions::default() };
+
+        let exact = |text : &str| from_str_with(text, precise);
+
+        // Kept digits compare exactly, against each other and against the
+        // exact value of a float.
+        assert_ne!(exact("0.1")?, exact("0.10000000000000001")?);
+        assert_ne!(exact("1.00000000000000000001")?, exact("1")?);
+        assert_eq!(exact("0.1")?, from_str("0.1")?);
+        assert_eq!(exact("0.1")?, json!(0.1));
+        assert_ne!(exact("0.10000000000000001")?, json!(0.1));
+        assert_eq!(exact("0.1000000000000000055511151231257827021181583404541015625")?, from_str("0.1")?);
+
+        let prices = r#"{"price" : 0.1, "fee" : 2.50, "count" : 3, "rate" : 1e-7}"#;
+        assert_eq!(exact(prices)?, from_str(prices)?);
+        assert_eq!(exact(prices)?, json!({ "price" : 0.1, "fee" : 2.5, "count" : 3.0, "rate" : 0.0000001 }));
+        assert_eq!(exact(prices)?["price"], json!(0.1));
+        assert_eq!(exact(prices)?["fee"], json!(2.5));
+        assert_eq!(exact("0.100")?, exact("1e-1")?);
+        assert_eq!(exact("-0.0")?, json!(0));
+        assert_eq!(exact("1e400")?, exact("10e399")?);
+        assert_eq!(exact("18446744073709551616")?, Member::from(2f64.powi(64)));
+        assert_eq!(exact("123456789012345678901234567890")?, exact("1234567890123456789012345678.9e2")?);
+        assert_ne!(exact("123456789012345678901234567890")?, exact("123456789012345678901234567891")?);
+
+        let hash = |member : &Member| {
+            let mut hasher = DefaultHasher::new();
+            member.hash(&mut hasher);
+            hasher.finish()
+        };
 
-        let json: JSON = parse_json(&mut reader)?;
+        assert_eq!(hash(&first), hash(&reordered));
+        assert_eq!(hash(&json!([1, 2.0])), hash(&json!([1.0, 2])));
+        assert_eq!(hash(&exact("1e400")?), hash(&exact("10e399")?));
+        assert_eq!(hash(&exact("18446744073709551616")?), hash(&Member::from(2f64.powi(64))));
+        assert_eq!(hash(&exact("0.5")?), hash(&json!(0.5)));
+        assert_eq!(hash(&exact("0.1")?), hash(&json!(0.1)));
 
-        let field: &Member = json.map.get("arr").unwrap_or( &Member::Empty);
+        // Comparing and hashing numbers without kept digits must stay about
+        // as cheap as comparing the doubles themselves.
+        let floats : String = (0..100_000).map(|i| format!("{}.{},", i, i % 977)).collect();
+        let floats = format!("[{}0.5]", floats);
+        let (left, right) = (from_str(&floats)?, from_str(&floats)?);
 
-        match field {
-            Member::Arr(arr)=>{
-                let uint = arr.get(0).unwrap_or(&Member::Empty);
-                match uint {
-                    Member::Num(num)=>{
-                        match num.value {
-                            Num::Uint(uint)=>{
-                                assert!(uint == 1);
-                            }
-                            _=>()
-                        }
-                    }
-                    _ =>()
-                };
-
-                let int = arr.get(1).unwrap_or(&Member::Empty);
-                match int {
-                    Member::Num(num)=>{
-                        match num.value {
-                            Num::Int(int)=>{
-                                assert!(int == -1);
-                            }
-                            _=>()
-                        }
-                    }
-                    _ =>()
-                };
-
-                let flt = arr.get(2).unwrap_or(&Member::Empty);
-                match flt {
-                    Member::Num(num)=>{
-                        match num.value {
-                            Num::Flt(fltr)=>{
-                                assert!(fltr == 22.0);
-                            }
-                            _=>()
-                        }
-                    }
-                    _ =>()
-                };
+        let started = std::time::Instant::now();
+        assert_eq!(left, right);
+        assert_eq!(hash(&left), hash(&right));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1), "took {:?}", started.elapsed());
 
-                let stringer = arr.get(2).unwrap_or(&Member::Empty);
-                match stringer {
-                    Member::Str(str)=>{
-                        assert!(str == "jimmy");
-                    }
-                    _ =>()
-                }
+        let documents : HashSet<Member> = [first.clone(), reordered, json!({ "name" : "beta" })].into_iter().collect();
+        assert_eq!(documents.len(), 2);
+        assert!(documents.contains(&first));
 
-                let booler = arr.get(2).unwrap_or(&Member::Empty);
-                match booler {
-                    Member::Bool(booly)=>{
-                        assert!(booly.value);
-                    }
-                    _ =>()
-                }
-            }
-            _=>{
-               return Err(anyhow!("No array found")); 
-            }
-        };
+        let mut counts : HashMap<Member, usize> = HashMap::new();
+
+        for port in [json!(80), json!(80.0), json!(443), json!(8e1)] {
+            *counts.entry(port).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts.get(&json!(80)), Some(&3));
+        assert_eq!(counts.get(&json!(443)), Some(&1));
 
         Ok(())
     }
 }
-
-
@@ -1,24 +1,24 @@
-use std::env;
-
-use anyhow::{self, Result};
+use std::io::Read;
+use std::process;
 
+use anyhow::Result;
 use painful_json::*;
-
-
-
 fn main() -> Result<()> {
-   
-    let mut reader = get_reader()?;
-
-
-    let json = parse_json(&mut reader)?;
-
-    let field = json.map.get("inter").unwrap_or(&JSONField::Empty);
-
+    let mut source = Vec::new();
+    get_reader()?.read_to_end(&mut source)?;
+
+    let document = match from_slice(&source) {
+        Ok(document) => document,
+        Err(err) => {
+            eprintln!("{}", err.render(&String::from_utf8_lossy(&source)));
+            process::exit(1);
+        }
+    };
+
+    let field = match &document {
+        Member::JSON(json) => json.map.get("inter").unwrap_or(&Member::Null),
+        _ => &Member::Null,
+    };
     println!("{}",field);
-
     Ok(())
 }
-
-
-